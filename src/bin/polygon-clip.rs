@@ -0,0 +1,222 @@
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use polygon_clip::boolean::{boolean_op, BooleanOp};
+use polygon_clip::edge::Edge;
+use polygon_clip::io::dxf::to_dxf;
+use polygon_clip::io::geojson::{clip_features, parse_geojson, to_geojson, Feature};
+use polygon_clip::io::{read_polygon, write_polygon, Format, Geometry};
+use polygon_clip::render::{render_scene, RenderOptions};
+use polygon_clip::validation::validate_polygon;
+
+const USAGE: &str = "\
+usage: polygon-clip <intersect|union|difference|xor> <subject> <clip> [options]
+
+files are read and written in the format given by their extension:
+.wkt, .wkb (binary or hex), .geojson or .json, .svg and .dxf.
+a GeoJSON subject written to GeoJSON keeps its features and their properties.
+
+options:
+  -o, --output <file>    where to write the result (default: standard output)
+  -f, --format <format>  format of standard input and output, written as '-' (default: wkt)
+  -t, --tolerance <t>    how far flattened curves may stray from the outline (default: 0.1)
+  -l, --layer <name>     layer of the DXF output (default: 0)
+  -p, --png <file>       also draw the subject, clip and result polygons to a PNG image
+      --size <w>x<h>     size of the PNG image, at most 16384x16384 (default: 1600x900)
+  -h, --help             show this message";
+
+/// the largest width and height of the PNG image
+const MAX_IMAGE_SIZE: u32 = 16384;
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    op: BooleanOp,
+    subject: String,
+    clipping: String,
+    output: String,
+    format: Format,
+    tolerance: f32,
+    layer: String,
+    png: Option<String>,
+    size: (u32, u32)
+}
+
+/// None if help was asked for
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut positional = vec![];
+    let (mut output, mut format, mut tolerance, mut layer) = ("-".to_string(), Format::Wkt, 0.1, "0".to_string());
+    let (mut png, mut size) = (None, (1600, 900));
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = value()?,
+            "-f" | "--format" => {
+                let name = value()?;
+                format = Format::from_name(&name).ok_or_else(|| format!("unknown format {}", name))?;
+            }
+            "-t" | "--tolerance" => {
+                let t = value()?;
+                tolerance = t.parse::<f32>().ok().filter(|t| *t > 0.0)
+                    .ok_or_else(|| format!("tolerance must be a positive number, not {}", t))?;
+            }
+            "-l" | "--layer" => layer = value()?,
+            "-p" | "--png" => png = Some(value()?),
+            "--size" => {
+                let s = value()?;
+                size = s.split_once('x')
+                    .and_then(|(w, h)| Some((w.parse::<u32>().ok()?, h.parse::<u32>().ok()?)))
+                    .filter(|(w, h)| *w > 0 && *h > 0)
+                    .ok_or_else(|| format!("size must look like 800x600, not {}", s))?;
+                if size.0 > MAX_IMAGE_SIZE || size.1 > MAX_IMAGE_SIZE {
+                    return Err(format!("size must be at most {}x{}, not {}", MAX_IMAGE_SIZE, MAX_IMAGE_SIZE, s))
+                }
+            }
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option {}", arg)),
+            _ => positional.push(arg)
+        }
+    }
+    let [op, subject, clipping]: [String; 3] = positional.try_into()
+        .map_err(|p: Vec<String>| format!("expected an operation and 2 files, got {} arguments", p.len()))?;
+    let op = match op.as_str() {
+        "intersect" => BooleanOp::Intersection,
+        "union" => BooleanOp::Union,
+        "difference" => BooleanOp::Difference,
+        "xor" => BooleanOp::Xor,
+        _ => return Err(format!("unknown operation {}", op))
+    };
+    if subject == "-" && clipping == "-" {
+        return Err("only one input can be read from standard input".to_string())
+    }
+    Ok(Some(Args { op, subject, clipping, output, format, tolerance, layer, png, size }))
+}
+
+/// '-' is standard input or output, in the format given on the command line
+fn format_of(path: &str, args: &Args) -> Result<Format, String> {
+    if path == "-" { return Ok(args.format) }
+    Format::from_path(Path::new(path)).ok_or_else(|| format!("{}: unknown file extension", path))
+}
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    let mut data = vec![];
+    let res = if path == "-" { std::io::stdin().read_to_end(&mut data).map(|_| ()) } else { std::fs::read(path).map(|d| data = d) };
+    res.map_err(|e| format!("{}: {}", path, e))?;
+    Ok(data)
+}
+
+fn read_input(path: &str, data: &[u8], args: &Args) -> Result<Vec<Edge>, String> {
+    let polygon = read_polygon(data, format_of(path, args)?, args.tolerance)
+        .map_err(|e| format!("{}: {}", path, e))?;
+    validate_polygon(&polygon).map_err(|e| format!("{}: invalid polygon: {}", path, e))?;
+    Ok(polygon)
+}
+
+/// all the polygons of the features as one edge list
+fn features_polygon(features: &[Feature]) -> Vec<Edge> {
+    features.iter().filter_map(|f| f.geometry.as_ref()).flat_map(Geometry::to_polygon).collect()
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let output_format = format_of(&args.output, args)?;
+    let subject_data = read(&args.subject)?;
+    // a GeoJSON subject written to GeoJSON is clipped feature by feature
+    let features = if format_of(&args.subject, args)? == Format::GeoJson && output_format == Format::GeoJson {
+        let features = std::str::from_utf8(&subject_data)
+            .map_err(|_| "the file is not valid UTF-8".to_string())
+            .and_then(|text| parse_geojson(text).map_err(|e| e.to_string()))
+            .map_err(|e| format!("{}: {}", args.subject, e))?;
+        for (i, feature) in features.iter().enumerate() {
+            let polygon = feature.geometry.as_ref().map_or(vec![], Geometry::to_polygon);
+            validate_polygon(&polygon)
+                .map_err(|e| format!("{}: invalid polygon in feature {}: {}", args.subject, i, e))?;
+        }
+        Some(features)
+    } else {
+        None
+    };
+    let subject = match &features {
+        Some(features) => features_polygon(features),
+        None => read_input(&args.subject, &subject_data, args)?
+    };
+    let clipping = read_input(&args.clipping, &read(&args.clipping)?, args)?;
+    let (result, data) = match &features {
+        Some(features) => {
+            let clipped = clip_features(features, &clipping, args.op);
+            (features_polygon(&clipped), to_geojson(&clipped).into_bytes())
+        }
+        None => {
+            let result = boolean_op(&subject, &clipping, args.op);
+            let data = match output_format {
+                Format::Dxf => to_dxf(&result, &args.layer).into_bytes(),
+                format => write_polygon(&result, format)
+            };
+            (result, data)
+        }
+    };
+    let res = if args.output == "-" { std::io::stdout().write_all(&data) } else { std::fs::write(&args.output, data) };
+    res.map_err(|e| format!("{}: {}", args.output, e))?;
+    if let Some(png) = &args.png {
+        let options = RenderOptions::fit(&[&subject, &clipping], args.size.0, args.size.1);
+        render_scene(&subject, &clipping, &result, args.op, &options).save_png(Path::new(png))
+            .map_err(|e| format!("{}: {}", png, e))?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)) {
+        Ok(None) => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        Ok(Some(args)) => match run(&args) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(message) => {
+                eprintln!("polygon-clip: {}", message);
+                ExitCode::FAILURE
+            }
+        },
+        Err(message) => {
+            eprintln!("polygon-clip: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polygon_clip::boolean::BooleanOp;
+    use polygon_clip::io::Format;
+
+    use crate::{parse_args, Args};
+
+    fn args(line: &str) -> Result<Option<Args>, String> {
+        parse_args(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(args("xor a.svg b.dxf -o out.geojson -t 0.5 --layer CUT").unwrap(), Some(Args {
+            op: BooleanOp::Xor,
+            subject: "a.svg".to_string(),
+            clipping: "b.dxf".to_string(),
+            output: "out.geojson".to_string(),
+            format: Format::Wkt,
+            tolerance: 0.5,
+            layer: "CUT".to_string(),
+            png: None,
+            size: (1600, 900)
+        }));
+        assert_eq!(args("union a.wkt b.wkt --png a.png --size 80x60").unwrap().unwrap().size, (80, 60));
+        assert_eq!(args("union a.wkt b.wkt --size 80").unwrap_err(), "size must look like 800x600, not 80");
+        assert_eq!(args("union a.wkt b.wkt --size 70000x70000").unwrap_err(), "size must be at most 16384x16384, not 70000x70000");
+        assert_eq!(args("intersect - b.wkt --format geojson").unwrap().unwrap().format, Format::GeoJson);
+        assert_eq!(args("union a.wkt --help").unwrap(), None);
+        assert_eq!(args("clip a.wkt b.wkt").unwrap_err(), "unknown operation clip");
+        assert_eq!(args("union a.wkt").unwrap_err(), "expected an operation and 2 files, got 2 arguments");
+        assert_eq!(args("union a.wkt b.wkt -o").unwrap_err(), "-o needs a value");
+        assert_eq!(args("union - -").unwrap_err(), "only one input can be read from standard input");
+    }
+}
@@ -0,0 +1,336 @@
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+
+use speedy2d::dimen::{Vec2, Vector2};
+
+use crate::edge::Edge;
+use crate::polygon::{is_point_in_polygon, winding_number};
+use crate::vec::{cross_product, inner_product};
+
+/// relative tolerance for merging vertices, scaled by the size of the input
+const SNAP_TOLERANCE: f64 = 1e-6;
+/// how far (in snap tolerances) beside an edge we look to decide which side is filled
+const SIDE_OFFSET: f64 = 100.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    Intersection,
+    Union,
+    Difference,
+    Xor
+}
+
+/// which points a set of rings encloses, as in SVG and PostScript
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// inside an odd number of rings
+    EvenOdd,
+    /// the rings wind around the point a non-zero number of times
+    NonZero
+}
+
+impl FillRule {
+    /// whether a point that the rings wind around `winding` times is inside
+    pub fn fills(&self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => winding % 2 != 0,
+            FillRule::NonZero => winding != 0
+        }
+    }
+}
+
+impl BooleanOp {
+    fn keeps(&self, in_subject: bool, in_clipping: bool) -> bool {
+        match self {
+            BooleanOp::Intersection => in_subject && in_clipping,
+            BooleanOp::Union => in_subject || in_clipping,
+            BooleanOp::Difference => in_subject && !in_clipping,
+            BooleanOp::Xor => in_subject != in_clipping
+        }
+    }
+}
+
+/// unlike `clip_polygon`, this does not walk the boundaries.
+/// all edges are split at every intersection, and a piece of edge is kept
+/// iff the result is filled on exactly one side of it.
+/// shared vertices and overlapping edges are therefore fine.
+/// the result uses the same orientation as the input: counter-clockwise outer rings, clockwise holes
+pub fn boolean_op (subject: &[Edge], clipping: &[Edge], op: BooleanOp) -> Vec<Edge> {
+    overlay(&[subject, clipping], |p| op.keeps(is_point_in_polygon(p, subject), is_point_in_polygon(p, clipping)))
+}
+
+/// union of any number of polygons
+pub fn union_all (polygons: &[Vec<Edge>]) -> Vec<Edge> {
+    let operands: Vec<&[Edge]> = polygons.iter().map(|p| p.as_slice()).collect();
+    overlay(&operands, |p| operands.iter().any(|o| is_point_in_polygon(p, o)))
+}
+
+/// the area that `rings` fill under `rule`, as a valid polygon.
+/// the rings may be oriented either way, overlap each other and cross themselves
+pub fn resolve_fill (rings: &[Vec<Edge>], rule: FillRule) -> Vec<Edge> {
+    let operands: Vec<&[Edge]> = rings.iter().map(|r| r.as_slice()).collect();
+    overlay(&operands, |p| rule.fills(rings.iter().map(|r| winding_number(p, r)).sum()))
+}
+
+type Point = Vector2<f64>;
+
+fn to_f64(v: Vec2) -> Point {
+    Vector2::new(v.x as f64, v.y as f64)
+}
+
+fn to_f32(v: Point) -> Vec2 {
+    Vec2::new(v.x as f32, v.y as f32)
+}
+
+fn length(v: Point) -> f64 {
+    inner_product(&v, &v).sqrt()
+}
+
+/// merges points closer than `eps`, using a grid with cells of size `eps`
+struct VertexPool {
+    points: Vec<Point>,
+    grid: HashMap<(i64, i64), Vec<usize>>,
+    eps: f64
+}
+
+impl VertexPool {
+    fn insert(&mut self, point: Point) -> usize {
+        let cell = ((point.x / self.eps).floor() as i64, (point.y / self.eps).floor() as i64);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let Some(ids) = self.grid.get(&(cell.0 + dx, cell.1 + dy)) {
+                    if let Some(id) = ids.iter().find(|id| length(self.points[**id] - point) <= self.eps) {
+                        return *id
+                    }
+                }
+            }
+        }
+        self.points.push(point);
+        self.grid.entry(cell).or_default().push(self.points.len() - 1);
+        self.points.len() - 1
+    }
+}
+
+/// parameters along `a` and `b` where they touch, including the ends of overlapping parts
+fn split_params(a: (Point, Point), b: (Point, Point), eps: f64) -> (Vec<f64>, Vec<f64>) {
+    let (va, vb) = (a.1 - a.0, b.1 - b.0);
+    let (len_a, len_b) = (length(va), length(vb));
+    let denom = cross_product(&va, &vb);
+    let (mut ta, mut tb) = (vec![], vec![]);
+    if denom.abs() <= 1e-12 * len_a * len_b {
+        // parallel. only overlapping collinear edges split each other
+        if cross_product(&va, &(b.0 - a.0)).abs() / len_a > eps { return (ta, tb) }
+        for p in [b.0, b.1] {
+            let t = inner_product(&(p - a.0), &va) / (len_a * len_a);
+            if t * len_a > eps && (1.0 - t) * len_a > eps { ta.push(t) }
+        }
+        for p in [a.0, a.1] {
+            let t = inner_product(&(p - b.0), &vb) / (len_b * len_b);
+            if t * len_b > eps && (1.0 - t) * len_b > eps { tb.push(t) }
+        }
+    } else {
+        let t = cross_product(&(b.0 - a.0), &vb) / denom;
+        let u = cross_product(&(b.0 - a.0), &va) / denom;
+        let on_a = t * len_a >= -eps && (1.0 - t) * len_a >= -eps;
+        let on_b = u * len_b >= -eps && (1.0 - u) * len_b >= -eps;
+        if on_a && on_b {
+            ta.push(t.clamp(0.0, 1.0));
+            tb.push(u.clamp(0.0, 1.0));
+        }
+    }
+    (ta, tb)
+}
+
+/// counter-clockwise angle (in the maths sense) from `from` to `to`, in (0, 2pi]
+fn turn_angle(from: Point, to: Point) -> f64 {
+    let angle = cross_product(&from, &to).atan2(inner_product(&from, &to));
+    if angle <= 0.0 { angle + 2.0 * PI } else { angle }
+}
+
+fn overlay<F: Fn(Vec2) -> bool>(operands: &[&[Edge]], is_filled: F) -> Vec<Edge> {
+    let segments: Vec<(Point, Point)> = operands.iter()
+        .flat_map(|p| p.iter())
+        .map(|e| (to_f64(e.from), to_f64(e.to)))
+        .filter(|(from, to)| from != to)
+        .collect();
+    if segments.is_empty() { return vec![] }
+    let scale = segments.iter()
+        .flat_map(|(from, to)| [from.x.abs(), from.y.abs(), to.x.abs(), to.y.abs()])
+        .fold(1.0, f64::max);
+    let eps = scale * SNAP_TOLERANCE;
+
+    // 1. split every edge at all the points where it meets another edge
+    let mut params = vec![vec![0.0, 1.0]; segments.len()];
+    for i in 0..segments.len() {
+        for j in (i + 1)..segments.len() {
+            let (ti, tj) = split_params(segments[i], segments[j], eps);
+            params[i].extend(ti);
+            params[j].extend(tj);
+        }
+    }
+    let mut pool = VertexPool { points: vec![], grid: HashMap::new(), eps };
+    let mut pieces = vec![];
+    let mut seen = HashSet::new();
+    for ((from, to), ts) in segments.iter().zip(params.iter_mut()) {
+        ts.sort_by(f64::total_cmp);
+        let ids: Vec<usize> = ts.iter().map(|t| pool.insert(*from + (*to - *from) * *t)).collect();
+        for (a, b) in ids.iter().zip(ids.iter().skip(1)) {
+            // overlapping edges give the same piece more than once
+            if a != b && seen.insert((*a.min(b), *a.max(b))) {
+                pieces.push((*a, *b));
+            }
+        }
+    }
+    let points = pool.points;
+
+    // 2. keep the pieces with the result on exactly one side, oriented so that
+    // the result lies where the cross product is negative (counter-clockwise with y downwards)
+    let boundary: Vec<(usize, usize)> = pieces.into_iter().filter_map(|(a, b)| {
+        let v = points[b] - points[a];
+        let len = length(v);
+        let normal = Vector2::new(-v.y, v.x) / len;
+        let offset = (len / 4.0).min(eps * SIDE_OFFSET);
+        let mid = (points[a] + points[b]) / 2.0;
+        let outer_side = is_filled(to_f32(mid + normal * offset));
+        let inner_side = is_filled(to_f32(mid - normal * offset));
+        match (inner_side, outer_side) {
+            (true, false) => Some((a, b)),
+            (false, true) => Some((b, a)),
+            _ => None
+        }
+    }).collect();
+
+    // 3. chain the pieces into rings. where several rings meet at a vertex,
+    // take the edge turning closest around the filled side so that the rings stay separate
+    let mut outgoing: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (i, (a, _)) in boundary.iter().enumerate() {
+        outgoing.entry(*a).or_default().push(i);
+    }
+    let mut used = vec![false; boundary.len()];
+    let mut res = vec![];
+    for start in 0..boundary.len() {
+        if used[start] { continue }
+        used[start] = true;
+        let mut ring = vec![boundary[start].0];
+        let mut cur = start;
+        let closed = loop {
+            let (from, to) = boundary[cur];
+            if to == boundary[start].0 { break true }
+            ring.push(to);
+            let back = points[from] - points[to];
+            let next = outgoing.get(&to).and_then(|candidates| candidates.iter()
+                .filter(|i| !used[**i])
+                .min_by(|i, j| turn_angle(back, points[boundary[**i].1] - points[to])
+                    .total_cmp(&turn_angle(back, points[boundary[**j].1] - points[to]))));
+            match next {
+                Some(i) => { used[*i] = true; cur = *i; }
+                None => break false
+            }
+        };
+        if !closed { continue }
+        let ring = remove_collinear(ring.iter().map(|i| points[*i]).collect(), eps);
+        if ring.len() < 3 { continue }
+        res.extend(ring.iter().zip(ring.iter().cycle().skip(1))
+            .map(|(from, to)| Edge { from: to_f32(*from), to: to_f32(*to) }));
+    }
+    res
+}
+
+/// drop vertices lying on the straight line between their neighbours
+fn remove_collinear(mut ring: Vec<Point>, eps: f64) -> Vec<Point> {
+    let mut i = 0;
+    while ring.len() >= 3 && i < ring.len() {
+        let prev = ring[(i + ring.len() - 1) % ring.len()];
+        let next = ring[(i + 1) % ring.len()];
+        let (v1, v2) = (ring[i] - prev, next - ring[i]);
+        if cross_product(&v1, &v2).abs() <= eps * length(next - prev) && inner_product(&v1, &v2) > 0.0 {
+            ring.remove(i);
+            i = i.saturating_sub(1);
+        } else {
+            i += 1;
+        }
+    }
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::boolean::{boolean_op, resolve_fill, union_all, BooleanOp, FillRule};
+    use crate::fixtures::{area, rect};
+    use crate::generator::{random_points, random_space_partitioning, random_star, random_with_holes, Rng};
+    use crate::polygon::{point_vec_to_edges, reverse_ring, split_rings};
+
+    #[test]
+    fn test_overlapping_squares() {
+        let a = rect(0.0, 0.0, 2.0, 2.0);
+        let b = rect(1.0, 1.0, 3.0, 3.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Intersection)), 1.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Union)), 7.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Difference)), 3.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Xor)), 6.0);
+        assert_eq!(split_rings(&boolean_op(&a, &b, BooleanOp::Xor)).len(), 2);
+    }
+
+    #[test]
+    fn test_shared_edges() {
+        let a = rect(0.0, 0.0, 1.0, 1.0);
+        let b = rect(1.0, 0.0, 2.0, 1.0);
+        let union = boolean_op(&a, &b, BooleanOp::Union);
+        assert_eq!(union.len(), 4, "collinear edges should be merged");
+        assert_eq!(area(&union), 2.0);
+        assert!(boolean_op(&a, &b, BooleanOp::Intersection).is_empty());
+        assert_eq!(area(&boolean_op(&a, &a, BooleanOp::Union)), 1.0);
+        assert!(boolean_op(&a, &a, BooleanOp::Difference).is_empty());
+    }
+
+    #[test]
+    fn test_holes() {
+        let mut a = rect(0.0, 0.0, 4.0, 4.0);
+        let hole = rect(1.0, 1.0, 3.0, 3.0);
+        a.extend(reverse_ring(&hole));
+        let b = rect(2.0, -1.0, 5.0, 5.0);
+        assert_eq!(area(&a), 12.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Intersection)), 6.0);
+        assert_eq!(area(&boolean_op(&a, &b, BooleanOp::Union)), 4.0 * 2.0 + 3.0 * 6.0 - 2.0);
+        let disjoint = rect(10.0, 10.0, 11.0, 11.0);
+        assert!(boolean_op(&a, &disjoint, BooleanOp::Intersection).is_empty());
+        assert_eq!(area(&union_all(&[a.clone(), disjoint, hole])), 17.0);
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // two squares drawn the same way round, one inside the other
+        let rings = [rect(0.0, 0.0, 4.0, 4.0), rect(1.0, 1.0, 3.0, 3.0)];
+        assert_eq!(area(&resolve_fill(&rings, FillRule::EvenOdd)), 12.0);
+        assert_eq!(area(&resolve_fill(&rings, FillRule::NonZero)), 16.0);
+        // a bow tie becomes two triangles in the crate's orientation
+        let bow_tie = point_vec_to_edges(&[
+            Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 2.0, y: 2.0 }, Vec2 { x: 2.0, y: 0.0 }, Vec2 { x: 0.0, y: 2.0 }
+        ]);
+        let res = resolve_fill(&[bow_tie], FillRule::NonZero);
+        assert_eq!(split_rings(&res).len(), 2);
+        assert_eq!(area(&res), 2.0);
+    }
+
+    #[test]
+    fn test_random_areas() {
+        for seed in 0..10 {
+            let mut rng = Rng::new(seed);
+            let points = random_points(&mut rng, 20, Vec2 { x: 0.0, y: 0.0 }, Vec2 { x: 100.0, y: 100.0 });
+            let a = random_space_partitioning(&mut rng, &points).unwrap();
+            let b = if seed % 2 == 0 {
+                random_star(&mut rng, 15, Vec2 { x: 50.0, y: 50.0 }, 20.0, 60.0)
+            } else {
+                random_with_holes(&mut rng, 15, 3, Vec2 { x: 50.0, y: 50.0 }, 40.0, 60.0)
+            };
+            let [intersection, union, difference, xor] = [BooleanOp::Intersection, BooleanOp::Union, BooleanOp::Difference, BooleanOp::Xor]
+                .map(|op| area(&boolean_op(&a, &b, op)));
+            let close = |x: f32, y: f32| (x - y).abs() < 1e-2 * y.abs().max(1.0);
+            assert!(close(union + intersection, area(&a) + area(&b)), "seed {}", seed);
+            assert!(close(difference + intersection, area(&a)), "seed {}", seed);
+            assert!(close(xor + intersection, union), "seed {}", seed);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::fmt;
 
 use speedy2d::dimen::Vec2;
 
@@ -14,12 +15,97 @@ struct IntersectionInfo<'a> {
     point: Vec2,
 }
 
+/// a step of `clip_polygon`, for showing how it got its result. edges are indices into the polygons
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    /// subject edge `subject_edge` crosses clipping edge `clipping_edge`, at the fractions `subject_t`
+    /// and `clipping_t` of the way along them. `entering` if the subject goes into the clipping
+    /// polygon there, which is where a walk along the subject starts
+    Intersection {
+        id: usize,
+        point: Vec2,
+        subject_edge: usize,
+        clipping_edge: usize,
+        subject_t: f32,
+        clipping_t: f32,
+        entering: bool
+    },
+    /// there are no intersections, so the result is whichever polygon is inside the other,
+    /// or nothing if neither is
+    NoIntersections { subject_inside: bool, clipping_inside: bool },
+    /// a walk around a ring of the result starts at unvisited intersection `id`
+    Start { id: usize, on_subject: bool },
+    /// the part of an edge from `from` to `to` goes into the result
+    Walk { on_subject: bool, edge: usize, from: Vec2, to: Vec2 },
+    /// the walk reaches intersection `id` and carries on along the other polygon
+    Switch { id: usize, to_subject: bool },
+    /// the walk is back at intersection `id`, which closes a ring of the result
+    Close { id: usize },
+    /// edges of one polygon that no walk went along, followed from a vertex inside the other.
+    /// they go into the result as a hole if they turn clockwise
+    Loop { on_subject: bool, edges: Vec<usize>, added: bool }
+}
+
+fn polygon_name(subject: bool) -> &'static str {
+    if subject { "subject" } else { "clipping" }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TraceEvent::Intersection { id, point, subject_edge, clipping_edge, subject_t, clipping_t, entering } =>
+                write!(f, "intersection {} at ({}, {}) of subject edge {} (t = {}) and clipping edge {} (t = {}), {}",
+                       id, point.x, point.y, subject_edge, subject_t, clipping_edge, clipping_t,
+                       if *entering { "in" } else { "out" }),
+            TraceEvent::NoIntersections { subject_inside: false, clipping_inside: false } =>
+                write!(f, "no intersections, the polygons are apart"),
+            TraceEvent::NoIntersections { subject_inside, .. } =>
+                write!(f, "no intersections, the {} polygon is inside", polygon_name(*subject_inside)),
+            TraceEvent::Start { id, on_subject } =>
+                write!(f, "start walking the {} polygon at intersection {}", polygon_name(*on_subject), id),
+            TraceEvent::Walk { on_subject, edge, from, to } =>
+                write!(f, "walk {} edge {} from ({}, {}) to ({}, {})", polygon_name(*on_subject), edge, from.x, from.y, to.x, to.y),
+            TraceEvent::Switch { id, to_subject } =>
+                write!(f, "switch to the {} polygon at intersection {}", polygon_name(*to_subject), id),
+            TraceEvent::Close { id } => write!(f, "ring closed at intersection {}", id),
+            TraceEvent::Loop { on_subject, edges, added } =>
+                write!(f, "{} loop of {} edges {:?}, {}", polygon_name(*on_subject), edges.len(), edges,
+                       if *added { "added as a hole" } else { "skipped, it is not clockwise" })
+        }
+    }
+}
+
+/// receives the steps of `clip_polygon_observed` as they happen,
+/// so that a clip that goes wrong or panics can still be followed up to there
+pub trait ClipObserver {
+    fn observe(&mut self, event: TraceEvent);
+}
+
+/// keeps all the steps
+impl ClipObserver for Vec<TraceEvent> {
+    fn observe(&mut self, event: TraceEvent) {
+        self.push(event)
+    }
+}
+
+impl<F: FnMut(TraceEvent)> ClipObserver for F {
+    fn observe(&mut self, event: TraceEvent) {
+        self(event)
+    }
+}
+
+/// how far along the edge the point is, from 0 at its start to 1 at its end
+fn edge_parameter(edge: &Edge, point: Vec2) -> f32 {
+    let length = edge.get_vector().magnitude();
+    if length <= f32::EPSILON { 0.0 } else { (point - edge.from).magnitude() / length }
+}
+
 struct IntersectionEdgeIdPair {
     sub_id: usize,
     clip_id: usize
 }
 
-struct ClippingData<'a> {
+struct ClippingData<'a, 'o> {
     intersect_list: Vec<IntersectionEdgeIdPair>,
     intersect_s: Vec<Vec<IntersectionInfo<'a>>>,
     intersect_c: Vec<Vec<IntersectionInfo<'a>>>,
@@ -27,27 +113,42 @@ struct ClippingData<'a> {
     clipping_polygon: Vec<Edge>,
     intersection_visited: HashSet<usize>,
     edge_vis_s: HashSet<usize>,
-    edge_vis_c: HashSet<usize>
+    edge_vis_c: HashSet<usize>,
+    observer: &'o mut dyn ClipObserver
+}
+
+impl ClippingData<'_, '_> {
+    fn walk(&mut self, on_subject: bool, edge: usize, part: Edge) {
+        self.observer.observe(TraceEvent::Walk { on_subject, edge, from: part.from, to: part.to });
+    }
+
+    fn switch(&mut self, id: usize, to_subject: bool) {
+        self.observer.observe(TraceEvent::Switch { id, to_subject });
+    }
 }
 
 fn search_edge (d: &mut ClippingData, walk_subject_edge: bool, edge_index: usize, intersect_id: usize) -> Vec<Edge> {
     if d.intersection_visited.contains(&intersect_id) {
+        d.observer.observe(TraceEvent::Close { id: intersect_id });
         return vec![]
     } else {
         d.intersection_visited.insert(intersect_id);
     }
-    return if walk_subject_edge {
+    if walk_subject_edge {
         d.edge_vis_s.insert(edge_index);
         let cur_inter_pos = d.intersect_s[edge_index].iter().position(|x| x.id == intersect_id).unwrap();
         let cur_inter = d.intersect_s[edge_index][cur_inter_pos];
-        if let Some(out_inter) = d.intersect_s[edge_index].get(cur_inter_pos + 1) {
+        if let Some(out_inter) = d.intersect_s[edge_index].get(cur_inter_pos + 1).copied() {
             // case 1. out vertex also on this edge
             let cur_edge = Edge { from: cur_inter.point, to: out_inter.point };
+            d.walk(true, edge_index, cur_edge);
+            d.switch(out_inter.id, false);
             let rest = search_edge(d, false, d.intersect_list[out_inter.id].clip_id, out_inter.id);
-            vec![cur_edge].into_iter().chain(rest.into_iter()).collect()
+            vec![cur_edge].into_iter().chain(rest).collect()
         } else {
             // case 2. out vertex is on the next edges
             let mut cur_edges = vec![Edge { from: cur_inter.point, to: d.subject_polygon[edge_index].to }];
+            d.walk(true, edge_index, cur_edges[0]);
             let mut rest = vec![];
             let mut expected_start_pos = d.subject_polygon[edge_index].to;
             for i in ((edge_index + 1)..(d.subject_polygon.len())).chain(0..=edge_index) {
@@ -57,28 +158,34 @@ fn search_edge (d: &mut ClippingData, walk_subject_edge: bool, edge_index: usize
                 }
                 if d.intersect_s[i].is_empty() {
                     cur_edges.push(d.subject_polygon[i]);
+                    d.walk(true, i, d.subject_polygon[i]);
                     expected_start_pos = d.subject_polygon[i].to;
                 } else {
-                    let it = d.intersect_s[i].first().unwrap();
+                    let it = *d.intersect_s[i].first().unwrap();
                     cur_edges.push(Edge { from: d.subject_polygon[i].from, to: it.point });
+                    d.walk(true, i, *cur_edges.last().unwrap());
+                    d.switch(it.id, false);
                     rest = search_edge(d, false, d.intersect_list[it.id].clip_id, it.id);
                     break
                 }
             }
-            cur_edges.into_iter().chain(rest.into_iter()).collect()
+            cur_edges.into_iter().chain(rest).collect()
         }
     } else {
         d.edge_vis_c.insert(edge_index);
         let cur_inter_pos = d.intersect_c[edge_index].iter().position(|x| x.id == intersect_id).unwrap();
         let cur_inter = d.intersect_c[edge_index][cur_inter_pos];
-        if let Some(in_inter) = d.intersect_c[edge_index].get(cur_inter_pos + 1) {
+        if let Some(in_inter) = d.intersect_c[edge_index].get(cur_inter_pos + 1).copied() {
             // case 1. in vertex also on this edge
             let cur_edge = Edge { from: cur_inter.point, to: in_inter.point };
+            d.walk(false, edge_index, cur_edge);
+            d.switch(in_inter.id, true);
             let rest = search_edge(d, true, d.intersect_list[in_inter.id].sub_id, in_inter.id);
-            vec![cur_edge].into_iter().chain(rest.into_iter()).collect()
+            vec![cur_edge].into_iter().chain(rest).collect()
         } else {
             // case 2. in vertex is on the next edges
             let mut cur_edges = vec![Edge { from: cur_inter.point, to: d.clipping_polygon[edge_index].to }];
+            d.walk(false, edge_index, cur_edges[0]);
             let mut rest = vec![];
             let mut expected_start_pos = d.clipping_polygon[edge_index].to;
             for i in ((edge_index + 1)..(d.clipping_polygon.len())).chain(0..=edge_index) {
@@ -88,20 +195,35 @@ fn search_edge (d: &mut ClippingData, walk_subject_edge: bool, edge_index: usize
                 }
                 if d.intersect_c[i].is_empty() {
                     cur_edges.push(d.clipping_polygon[i]);
+                    d.walk(false, i, d.clipping_polygon[i]);
                     expected_start_pos = d.clipping_polygon[i].to;
                 } else {
-                    let it = d.intersect_c[i].first().unwrap();
+                    let it = *d.intersect_c[i].first().unwrap();
                     cur_edges.push(Edge { from: d.clipping_polygon[i].from, to: it.point });
+                    d.walk(false, i, *cur_edges.last().unwrap());
+                    d.switch(it.id, true);
                     rest = search_edge(d, true, d.intersect_list[it.id].sub_id, it.id);
                     break
                 }
             }
-            cur_edges.into_iter().chain(rest.into_iter()).collect()
+            cur_edges.into_iter().chain(rest).collect()
         }
     }
 }
 
 pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec<Edge> {
+    clip_polygon_observed(subject_polygon, clipping_polygon, &mut |_| {})
+}
+
+/// `clip_polygon`, also returning the steps it took
+pub fn clip_polygon_traced (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> (Vec<Edge>, Vec<TraceEvent>) {
+    let mut trace = vec![];
+    let result = clip_polygon_observed(subject_polygon, clipping_polygon, &mut trace);
+    (result, trace)
+}
+
+/// `clip_polygon`, telling `observer` each step it takes
+pub fn clip_polygon_observed (subject_polygon: &[Edge], clipping_polygon: &[Edge], observer: &mut dyn ClipObserver) -> Vec<Edge> {
     let mut d = ClippingData {
         intersect_list: vec![],
         intersect_s: vec![vec![]; subject_polygon.len()],
@@ -110,7 +232,8 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
         clipping_polygon: clipping_polygon.to_vec(),
         intersection_visited: HashSet::new(),
         edge_vis_s: HashSet::new(),
-        edge_vis_c: HashSet::new()
+        edge_vis_c: HashSet::new(),
+        observer
     };
 
     for (i, e_sub) in subject_polygon.iter().enumerate() {
@@ -128,17 +251,28 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
                     other_edge: e_sub,
                     point: intersection,
                 });
+                d.observer.observe(TraceEvent::Intersection {
+                    id: d.intersect_list.len(),
+                    point: intersection,
+                    subject_edge: i,
+                    clipping_edge: j,
+                    subject_t: edge_parameter(e_sub, intersection),
+                    clipping_t: edge_parameter(e_clip, intersection),
+                    entering: cross_product(&e_sub.get_vector(), &e_clip.get_vector()) > 0.0
+                });
                 d.intersect_list.push(IntersectionEdgeIdPair { sub_id: i, clip_id: j });
             }
         }
     }
 
     if d.intersect_list.is_empty() {
-        // one polygon completely lies in another
-        // take a vertex from main polygon. see if it lies in the clipping polygon
-        // yes -> return main polygon; no -> return clipping polygon
-        let all_visible = is_point_in_polygon(d.subject_polygon.first().unwrap().from, &d.clipping_polygon);
-        if all_visible { d.subject_polygon } else { d.clipping_polygon }
+        // one polygon completely lies in another, or they are apart
+        // take a vertex from each polygon. see if it lies in the other one
+        let subject_inside = is_point_in_polygon(d.subject_polygon.first().unwrap().from, &d.clipping_polygon);
+        let clipping_inside = !subject_inside
+            && is_point_in_polygon(d.clipping_polygon.first().unwrap().from, &d.subject_polygon);
+        d.observer.observe(TraceEvent::NoIntersections { subject_inside, clipping_inside });
+        if subject_inside { d.subject_polygon } else if clipping_inside { d.clipping_polygon } else { vec![] }
     } else {
         let sort_func = |a: &IntersectionInfo, b: &IntersectionInfo|
             (a.point - a.this_edge.from).magnitude_squared()
@@ -152,6 +286,7 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
             for it in it_list.iter() {
                 if !d.intersection_visited.contains(&it.id) {
                     let is_in_edge = cross_product(&it.this_edge.get_vector(), &it.other_edge.get_vector()) > 0.0;
+                    d.observer.observe(TraceEvent::Start { id: it.id, on_subject: is_in_edge });
                     if is_in_edge {
                         res.extend( search_edge(&mut d, true, i, it.id))
                     } else {
@@ -161,12 +296,14 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
                 }
             }
         }
-        let mut add_loop = |poly: &[Edge], other_poly: &[Edge], edge_vis: &mut HashSet<usize>| {
+        let mut add_loop = |poly: &[Edge], other_poly: &[Edge], edge_vis: &mut HashSet<usize>,
+                            on_subject: bool, observer: &mut dyn ClipObserver| {
             for (i, edge) in poly.iter().enumerate() {
                 if edge_vis.contains(&i) || !is_point_in_polygon(edge.from, other_poly) {
                     continue
                 }
                 let mut loop_edges = vec![];
+                let mut loop_ids = vec![];
                 let mut expected_start_pos = edge.from;
                 for j in (i..(poly.len())).chain(0..i) {
                     let x = poly[j];
@@ -176,6 +313,7 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
                     }
                     edge_vis.insert(j);
                     loop_edges.push(x);
+                    loop_ids.push(j);
                     expected_start_pos = x.to;
                     if (loop_edges.last().unwrap().to - loop_edges.first().unwrap().from)
                         .magnitude_squared() <= f32::EPSILON {
@@ -183,13 +321,80 @@ pub fn clip_polygon (subject_polygon: &[Edge], clipping_polygon: &[Edge]) -> Vec
                     }
                 }
                 if loop_edges.len() < 3 { continue }
-                if is_polygon_clockwise(&loop_edges) {
+                let added = is_polygon_clockwise(&loop_edges);
+                observer.observe(TraceEvent::Loop { on_subject, edges: loop_ids, added });
+                if added {
                     res.extend(loop_edges)
                 }
             }
         };
-        add_loop(&d.subject_polygon, &d.clipping_polygon,&mut d.edge_vis_s);
-        add_loop(&d.clipping_polygon, &d.subject_polygon,&mut d.edge_vis_c);
+        add_loop(&d.subject_polygon, &d.clipping_polygon,&mut d.edge_vis_s, true, d.observer);
+        add_loop(&d.clipping_polygon, &d.subject_polygon,&mut d.edge_vis_c, false, d.observer);
         res
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::clipping::{clip_polygon, clip_polygon_observed, clip_polygon_traced, TraceEvent};
+    use crate::edge::Edge;
+    use crate::polygon::{point_vec_to_edges, reverse_ring};
+
+    fn square(x: f32, y: f32, size: f32) -> Vec<Edge> {
+        point_vec_to_edges(&[
+            Vec2 { x, y }, Vec2 { x, y: y + size }, Vec2 { x: x + size, y: y + size }, Vec2 { x: x + size, y }
+        ])
+    }
+
+    #[test]
+    fn test_trace() {
+        let (result, trace) = clip_polygon_traced(&square(0.0, 0.0, 2.0), &square(1.0, 1.0, 2.0));
+        assert_eq!(result, clip_polygon(&square(0.0, 0.0, 2.0), &square(1.0, 1.0, 2.0)));
+        assert_eq!(trace[0], TraceEvent::Intersection {
+            id: 0,
+            point: Vec2 { x: 1.0, y: 2.0 },
+            subject_edge: 1,
+            clipping_edge: 0,
+            subject_t: 0.5,
+            clipping_t: 0.5,
+            entering: true
+        });
+        assert!(matches!(trace[1], TraceEvent::Intersection { id: 1, entering: false, .. }));
+        assert_eq!(trace[2], TraceEvent::Start { id: 0, on_subject: true });
+        assert_eq!(trace[9], TraceEvent::Close { id: 0 });
+        // chains of edges that start on the other polygon's boundary turn the wrong way to be holes
+        assert!(trace[10..].iter().all(|e| matches!(e, TraceEvent::Loop { added: false, .. })));
+        // the walked parts are the result
+        let walked: Vec<Edge> = trace.iter().filter_map(|e| match e {
+            TraceEvent::Walk { from, to, .. } => Some(Edge { from: *from, to: *to }),
+            _ => None
+        }).collect();
+        assert_eq!(walked, result);
+        assert_eq!(trace[3].to_string(), "walk subject edge 1 from (1, 2) to (2, 2)");
+    }
+
+    #[test]
+    fn test_trace_loops() {
+        // the subject with a hole inside the clipping polygon, crossing it on the right
+        let mut subject = square(0.0, 0.0, 10.0);
+        subject.extend(reverse_ring(&square(2.0, 2.0, 2.0)));
+        let mut events = vec![];
+        clip_polygon_observed(&subject, &square(1.0, 1.0, 20.0), &mut |event| events.push(event));
+        assert!(events.contains(&TraceEvent::Loop { on_subject: true, edges: vec![4, 5, 6, 7], added: true }));
+        let (_, trace) = clip_polygon_traced(&square(1.0, 1.0, 1.0), &square(0.0, 0.0, 5.0));
+        assert_eq!(trace, vec![TraceEvent::NoIntersections { subject_inside: true, clipping_inside: false }]);
+    }
+
+    #[test]
+    fn test_no_intersections() {
+        let (inner, outer, apart) = (square(1.0, 1.0, 1.0), square(0.0, 0.0, 5.0), square(10.0, 10.0, 1.0));
+        assert_eq!(clip_polygon(&inner, &outer), inner);
+        assert_eq!(clip_polygon(&outer, &inner), inner);
+        let (result, trace) = clip_polygon_traced(&outer, &apart);
+        assert!(result.is_empty(), "polygons that are apart have no intersection");
+        assert_eq!(trace[0].to_string(), "no intersections, the polygons are apart");
+        assert!(clip_polygon(&apart, &outer).is_empty());
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::polygon::{group_rings, is_convex, point_vec_to_edges};
+use crate::triangulation::triangulate_group;
+use crate::vec::cross_product;
+
+/// splits a polygon with holes into convex rings (counter-clockwise, like outer rings).
+/// hertel-mehlhorn: triangulate, then drop every diagonal whose removal keeps both corners convex.
+/// this gives at most four times the minimum number of pieces
+pub fn convex_decomposition (polygon: &[Edge]) -> Vec<Vec<Edge>> {
+    let mut res = vec![];
+    for group in group_rings(polygon) {
+        if group.holes.is_empty() && is_convex(&group.outer) {
+            res.push(group.outer);
+            continue
+        }
+        let triangulation = triangulate_group(&group);
+        let triangles = triangulation.indices.chunks_exact(3)
+            .map(|t| t.iter().map(|i| *i as usize).collect())
+            .collect();
+        let pieces = merge_triangles(&triangulation.vertices, triangles);
+        res.extend(pieces.iter().map(|piece| {
+            point_vec_to_edges(&piece.iter().map(|i| triangulation.vertices[*i]).collect::<Vec<_>>())
+        }));
+    }
+    res
+}
+
+/// counter-clockwise with y downwards means convex corners turn with a non-positive cross product
+fn is_convex_corner(prev: Vec2, cur: Vec2, next: Vec2) -> bool {
+    cross_product(&(cur - prev), &(next - cur)) <= 0.0
+}
+
+fn merge_triangles(vertices: &[Vec2], triangles: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    let mut pieces: Vec<Option<Vec<usize>>> = triangles.into_iter().map(Some).collect();
+    // the piece each directed edge belongs to
+    let mut owner: HashMap<(usize, usize), usize> = HashMap::new();
+    for (id, piece) in pieces.iter().enumerate() {
+        let piece = piece.as_ref().unwrap();
+        for k in 0..piece.len() {
+            owner.insert((piece[k], piece[(k + 1) % piece.len()]), id);
+        }
+    }
+    // diagonals are the edges used in both directions
+    let mut diagonals: Vec<(usize, usize)> = owner.keys()
+        .filter(|(a, b)| a < b && owner.contains_key(&(*b, *a)))
+        .copied()
+        .collect();
+    diagonals.sort();
+    for (a, b) in diagonals {
+        let (p_id, q_id) = (owner[&(a, b)], owner[&(b, a)]);
+        if p_id == q_id { continue }
+        let (p, q) = (pieces[p_id].as_ref().unwrap(), pieces[q_id].as_ref().unwrap());
+        // p runs b .. a, q runs a .. b
+        let mut p_walk = p.clone();
+        p_walk.rotate_left(p.iter().position(|i| *i == b).unwrap());
+        let mut q_walk = q.clone();
+        q_walk.rotate_left(q.iter().position(|i| *i == a).unwrap());
+        let corner_a = is_convex_corner(vertices[p_walk[p_walk.len() - 2]], vertices[a], vertices[q_walk[1]]);
+        let corner_b = is_convex_corner(vertices[q_walk[q_walk.len() - 2]], vertices[b], vertices[p_walk[1]]);
+        if !corner_a || !corner_b { continue }
+        let merged: Vec<usize> = p_walk.iter().chain(q_walk[1..(q_walk.len() - 1)].iter()).copied().collect();
+        owner.remove(&(a, b));
+        owner.remove(&(b, a));
+        for k in 0..merged.len() {
+            owner.insert((merged[k], merged[(k + 1) % merged.len()]), p_id);
+        }
+        pieces[p_id] = Some(merged);
+        pieces[q_id] = None;
+    }
+    pieces.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::decomposition::convex_decomposition;
+    use crate::edge::Edge;
+    use crate::fixtures::ring;
+    use crate::polygon::{is_convex, signed_area};
+
+    /// checks that every piece is a convex outer ring and returns the total area
+    fn pieces_area(pieces: &[Vec<Edge>]) -> f32 {
+        pieces.iter().map(|piece| {
+            assert!(is_convex(piece), "piece should be convex");
+            assert!(signed_area(piece) < 0.0, "piece should be counter-clockwise");
+            -signed_area(piece)
+        }).sum()
+    }
+
+    #[test]
+    fn test_simple() {
+        let square = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        assert_eq!(convex_decomposition(&square).len(), 1);
+        let l_shape = ring(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]);
+        let pieces = convex_decomposition(&l_shape);
+        assert_eq!(pieces.len(), 2);
+        assert_eq!(pieces_area(&pieces), 3.0);
+    }
+
+    #[test]
+    fn test_holes() {
+        let mut frame = ring(&[(0.0, 0.0), (0.0, 6.0), (6.0, 6.0), (6.0, 0.0)]);
+        frame.extend(ring(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]));
+        let pieces = convex_decomposition(&frame);
+        assert!(pieces.len() >= 4 && pieces.len() < 8, "got {} pieces", pieces.len());
+        assert_eq!(pieces_area(&pieces), 32.0);
+    }
+}
@@ -2,7 +2,7 @@ use speedy2d::dimen::Vec2;
 
 use crate::vec::{cross_product, dist_to_line, inner_product};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Edge {
     pub from: Vec2,
     pub to: Vec2
@@ -22,6 +22,14 @@ impl Edge {
         let on_edge = from_point_vec.magnitude_squared() <= self_vec.magnitude_squared();
         same_line && on_edge
     }
+    /// the point of the edge nearest to `point`
+    pub fn closest_point (&self, point: Vec2) -> Vec2 {
+        let v = self.get_vector();
+        let length_squared = v.magnitude_squared();
+        if length_squared <= f32::EPSILON { return self.from }
+        let k = (inner_product(&(point - self.from), &v) / length_squared).clamp(0.0, 1.0);
+        self.from + v * k
+    }
     pub fn intersect_with (&self, other: &Self) -> Option<Vec2> {
         let self_vec = self.get_vector();
         let other_vec = other.get_vector();
@@ -69,6 +77,14 @@ mod tests {
         assert!(! edge.has_point(&Vec2 { x: 2.0, y: 1.0 }), "fail on non-intersecting");
     }
 
+    #[test]
+    fn test_closest_point() {
+        let edge = Edge { from: Vec2 { x: 0.0, y: 0.0 }, to: Vec2 { x: 4.0, y: 0.0 } };
+        assert_eq!(edge.closest_point(Vec2 { x: 1.0, y: 3.0 }), Vec2 { x: 1.0, y: 0.0 });
+        assert_eq!(edge.closest_point(Vec2 { x: -2.0, y: 1.0 }), edge.from);
+        assert_eq!(edge.closest_point(Vec2 { x: 9.0, y: -1.0 }), edge.to);
+    }
+
     #[test]
     fn test_intersection() {
         {
@@ -0,0 +1,20 @@
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::polygon::{point_vec_to_edges, signed_area, split_rings};
+
+/// a ring through the points, in order
+pub fn ring(points: &[(f32, f32)]) -> Vec<Edge> {
+    let points: Vec<Vec2> = points.iter().map(|(x, y)| Vec2 { x: *x, y: *y }).collect();
+    point_vec_to_edges(&points)
+}
+
+/// a counter-clockwise rectangle, like an outer ring
+pub fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<Edge> {
+    ring(&[(x0, y0), (x0, y1), (x1, y1), (x1, y0)])
+}
+
+/// outer rings have negative signed area, holes positive
+pub fn area(polygon: &[Edge]) -> f32 {
+    -split_rings(polygon).iter().map(|ring| signed_area(ring)).sum::<f32>()
+}
@@ -0,0 +1,277 @@
+use std::f32::consts::PI;
+
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::polygon::{point_vec_to_edges, reverse_ring, signed_area};
+use crate::validation::edges_cross;
+use crate::vec::cross_product;
+
+/// splitmix64. small, but the same seed gives the same polygons on every platform and version
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// uniform in [min, max)
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// uniform in 0..n, which must not be empty
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "no number is below 0");
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// `n` points in the box from `min` to `max`
+pub fn random_points(rng: &mut Rng, n: usize, min: Vec2, max: Vec2) -> Vec<Vec2> {
+    (0..n).map(|_| Vec2::new(rng.range(min.x, max.x), rng.range(min.y, max.y))).collect()
+}
+
+/// turns a simple ring counter-clockwise, like an outer ring
+fn outer_ring(points: &[Vec2]) -> Vec<Edge> {
+    let ring = point_vec_to_edges(points);
+    if signed_area(&ring) > 0.0 { reverse_ring(&ring) } else { ring }
+}
+
+/// the angles of `n` vertices around a centre, one in each of `n` equal sectors,
+/// going counter-clockwise with y downwards
+fn star_angles(rng: &mut Rng, n: usize) -> Vec<f32> {
+    (0..n).map(|i| -(i as f32 + rng.range(0.05, 0.95)) * 2.0 * PI / n as f32).collect()
+}
+
+fn on_circle(center: Vec2, radius: f32, angle: f32) -> Vec2 {
+    center + Vec2::new(angle.cos(), angle.sin()) * radius
+}
+
+/// every vertex can be seen from `center`, at a distance between `min_radius` and `max_radius`.
+/// at least 3 vertices
+pub fn random_star(rng: &mut Rng, n: usize, center: Vec2, min_radius: f32, max_radius: f32) -> Vec<Edge> {
+    let points: Vec<Vec2> = star_angles(rng, n.max(3)).into_iter()
+        .map(|angle| on_circle(center, rng.range(min_radius, max_radius), angle))
+        .collect();
+    point_vec_to_edges(&points)
+}
+
+/// a simple ring through all the points, by space partitioning: the points are split by a line
+/// through a random pair of them, and each side is joined into a chain recursively.
+/// none for fewer than 3 points. the ring is not simple if they are all on one line
+pub fn random_space_partitioning(rng: &mut Rng, points: &[Vec2]) -> Option<Vec<Edge>> {
+    if points.len() < 3 { return None }
+    let mut points = points.to_vec();
+    rng.shuffle(&mut points);
+    let (first, last) = (points[0], points[1]);
+    let side = |p: &Vec2| cross_product(&(last - first), &(p - first));
+    let (left, right): (Vec<Vec2>, Vec<Vec2>) = points[2..].iter().partition(|p| side(p) > 0.0);
+    let mut ring = partition_chain(rng, left, first, last);
+    ring.extend(partition_chain(rng, right, last, first));
+    Some(outer_ring(&ring))
+}
+
+/// a chain from `from` through all the points, ending just before `to`. the points are split by a
+/// line through one of them that crosses the segment between `from` and `to`, so the chains on
+/// each side stay apart
+fn partition_chain(rng: &mut Rng, mut points: Vec<Vec2>, from: Vec2, to: Vec2) -> Vec<Vec2> {
+    if points.is_empty() { return vec![from] }
+    let pivot = points.swap_remove(rng.below(points.len()));
+    let towards = from + (to - from) * rng.range(0.1, 0.9);
+    let side = |p: &Vec2| cross_product(&(towards - pivot), &(p - pivot));
+    let from_side = side(&from) > 0.0;
+    let (before, after): (Vec<Vec2>, Vec<Vec2>) = points.into_iter().partition(|p| (side(p) > 0.0) == from_side);
+    let mut chain = partition_chain(rng, before, from, pivot);
+    chain.extend(partition_chain(rng, after, pivot, to));
+    chain
+}
+
+/// how many times `random_two_opt` reverses part of the ring before giving up
+const MAX_REVERSALS: usize = 10_000;
+
+/// a simple ring through all the points, by joining them in random order and then undoing
+/// crossings, reversing the part between two crossing edges, until there are none.
+/// none for fewer than 3 points, or if crossings are left after `MAX_REVERSALS`, which
+/// points on one line can cause
+pub fn random_two_opt(rng: &mut Rng, points: &[Vec2]) -> Option<Vec<Edge>> {
+    if points.len() < 3 { return None }
+    let mut points = points.to_vec();
+    rng.shuffle(&mut points);
+    let n = points.len();
+    'untangle: for _ in 0..=MAX_REVERSALS {
+        let ring = point_vec_to_edges(&points);
+        for i in 0..n {
+            for j in (i + 2)..n {
+                if i == 0 && j == n - 1 { continue }
+                if edges_cross(&ring[i], &ring[j]) {
+                    // i.to ... j.from are reversed, so i and j are replaced by i.from-j.from and i.to-j.to
+                    points[(i + 1)..=j].reverse();
+                    continue 'untangle
+                }
+            }
+        }
+        return Some(outer_ring(&points))
+    }
+    None
+}
+
+/// a star-shaped outer ring with `holes` star-shaped holes inside the circle it keeps clear
+/// around `center`, spread around it so that they do not touch
+pub fn random_with_holes(rng: &mut Rng, n: usize, holes: usize, center: Vec2, min_radius: f32, max_radius: f32) -> Vec<Edge> {
+    let mut polygon = random_star(rng, n, center, min_radius, max_radius);
+    // the sides of the outer ring cut into the circle, so keep to well within it
+    let clear = min_radius * (PI / n.max(3) as f32).cos();
+    let (orbit, size) = match holes {
+        0 => return polygon,
+        1 => (0.0, clear * 0.6),
+        _ => (clear * 0.5, clear * 0.45 * (PI / holes as f32).sin().min(1.0))
+    };
+    let start = rng.range(0.0, 2.0 * PI);
+    for i in 0..holes {
+        let hole_center = on_circle(center, orbit, start + i as f32 * 2.0 * PI / holes as f32);
+        let vertices = 3 + rng.below(5);
+        let hole = random_star(rng, vertices, hole_center, size * 0.4, size);
+        polygon.extend(reverse_ring(&hole));
+    }
+    polygon
+}
+
+/// two rectangles of the same height, the second shifted right by half their width. their top and
+/// bottom sides are on the same lines, and every side is split into `splits` collinear edges
+pub fn collinear_pair(rng: &mut Rng, splits: usize, min: Vec2, size: Vec2) -> (Vec<Edge>, Vec<Edge>) {
+    let mut rectangle = |min: Vec2| {
+        let corners = [min, min + Vec2::new(0.0, size.y), min + size, min + Vec2::new(size.x, 0.0)];
+        let points: Vec<Vec2> = (0..4).flat_map(|i| {
+            let (a, b) = (corners[i], corners[(i + 1) % 4]);
+            let mut ts: Vec<f32> = (1..splits.max(1)).map(|_| rng.range(0.05, 0.95)).collect();
+            ts.sort_by(|a, b| a.total_cmp(b));
+            ts.dedup();
+            std::iter::once(a).chain(ts.into_iter().map(move |t| a + (b - a) * t)).collect::<Vec<_>>()
+        }).collect();
+        point_vec_to_edges(&points)
+    };
+    let subject = rectangle(min);
+    (subject, rectangle(min + Vec2::new(size.x / 2.0, 0.0)))
+}
+
+/// two star-shaped rings around the same centre, sharing every other vertex
+pub fn shared_vertex_pair(rng: &mut Rng, n: usize, center: Vec2, min_radius: f32, max_radius: f32) -> (Vec<Edge>, Vec<Edge>) {
+    let angles = star_angles(rng, n.max(4));
+    let (subject, clipping): (Vec<Vec2>, Vec<Vec2>) = angles.iter().enumerate().map(|(i, angle)| {
+        let point = on_circle(center, rng.range(min_radius, max_radius), *angle);
+        let other = if i % 2 == 0 { point } else { on_circle(center, rng.range(min_radius, max_radius), *angle) };
+        (point, other)
+    }).unzip();
+    (point_vec_to_edges(&subject), point_vec_to_edges(&clipping))
+}
+
+/// a star-shaped ring, and the same ring turned by a tiny angle around a point near its centre,
+/// so that every edge is nearly parallel to its copy
+pub fn near_parallel_pair(rng: &mut Rng, n: usize, center: Vec2, min_radius: f32, max_radius: f32) -> (Vec<Edge>, Vec<Edge>) {
+    let subject = random_star(rng, n, center, min_radius, max_radius);
+    let pivot = center + Vec2::new(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0)) * (min_radius * 0.1);
+    let angle = rng.range(1e-4, 1e-3);
+    let (sin, cos) = angle.sin_cos();
+    let turn = |p: Vec2| {
+        let d = p - pivot;
+        pivot + Vec2::new(d.x * cos - d.y * sin, d.x * sin + d.y * cos)
+    };
+    let clipping = subject.iter().map(|e| Edge { from: turn(e.from), to: turn(e.to) }).collect();
+    (subject, clipping)
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::generator::{collinear_pair, near_parallel_pair, random_points, random_space_partitioning, random_star,
+                           random_two_opt, random_with_holes, shared_vertex_pair, Rng};
+    use crate::polygon::{signed_area, split_rings};
+    use crate::validation::validate_polygon;
+
+    const ORIGIN: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+    const CENTER: Vec2 = Vec2 { x: 50.0, y: 50.0 };
+
+    #[test]
+    fn test_rng() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let xs: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        assert_eq!(xs, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(xs[0], Rng::new(8).next_u64());
+        assert!((0..1000).map(|_| a.range(-1.0, 1.0)).all(|x| (-1.0..1.0).contains(&x)));
+        let mut items: Vec<usize> = (0..20).collect();
+        a.shuffle(&mut items);
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+        assert!((0..100).all(|_| a.below(3) < 3));
+    }
+
+    #[test]
+    fn test_too_few_points() {
+        let mut rng = Rng::new(1);
+        let points = random_points(&mut rng, 2, ORIGIN, Vec2::new(100.0, 100.0));
+        assert_eq!(random_space_partitioning(&mut rng, &points), None);
+        assert_eq!(random_two_opt(&mut rng, &points), None);
+        assert_eq!(random_two_opt(&mut rng, &[]), None);
+    }
+
+    #[test]
+    fn test_simple_polygons() {
+        for seed in 0..20 {
+            let mut rng = Rng::new(seed);
+            let star = random_star(&mut rng, 12, CENTER, 10.0, 40.0);
+            assert_eq!(validate_polygon(&star), Ok(()), "star, seed {}", seed);
+            assert!(signed_area(&star) < 0.0, "star should be an outer ring, seed {}", seed);
+
+            let points = random_points(&mut rng, 30, ORIGIN, Vec2::new(100.0, 100.0));
+            for ring in [random_space_partitioning(&mut rng, &points).unwrap(), random_two_opt(&mut rng, &points).unwrap()] {
+                assert_eq!(ring.len(), 30);
+                assert_eq!(validate_polygon(&ring), Ok(()), "seed {}", seed);
+                assert!(signed_area(&ring) < 0.0, "seed {}", seed);
+            }
+
+            let with_holes = random_with_holes(&mut rng, 10, 1 + seed as usize % 4, CENTER, 30.0, 45.0);
+            assert_eq!(split_rings(&with_holes).len(), 2 + seed as usize % 4);
+            assert_eq!(validate_polygon(&with_holes), Ok(()), "holes, seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_adversarial_pairs() {
+        let mut rng = Rng::new(3);
+        let (a, b) = collinear_pair(&mut rng, 6, ORIGIN, Vec2::new(10.0, 4.0));
+        assert!((signed_area(&a) + 40.0).abs() < 1e-3 && (signed_area(&b) + 40.0).abs() < 1e-3);
+        assert!(a.len() > 4 && b.len() > 4);
+        let (a, b) = shared_vertex_pair(&mut rng, 10, CENTER, 10.0, 40.0);
+        assert_eq!(a.iter().zip(b.iter()).filter(|(a, b)| a.from == b.from).count(), 5);
+        let (a, b) = near_parallel_pair(&mut rng, 10, CENTER, 10.0, 40.0);
+        assert!((signed_area(&a) - signed_area(&b)).abs() < 0.1);
+        for polygon in [a, b] {
+            assert_eq!(validate_polygon(&polygon), Ok(()));
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::{resolve_fill, FillRule};
+use crate::edge::Edge;
+use crate::io::ParseError;
+use crate::polygon::{point_vec_to_edges, split_rings};
+
+/// polyline flags
+const CLOSED: i32 = 1;
+const POLYGON_MESH: i32 = 16;
+const POLYFACE_MESH: i32 = 64;
+/// vertex flags
+const SPLINE_FRAME: i32 = 16;
+const POLYFACE_VERTEX: i32 = 128;
+
+/// reads the closed LWPOLYLINE and POLYLINE entities of the ENTITIES section, grouped by layer.
+/// bulges are flattened into arcs staying within `tolerance` of the true outline,
+/// and a polyline ending where it started counts as closed.
+/// the outlines on a layer are combined with the even-odd rule, so an outline inside another one is a hole.
+/// coordinates are kept as they are: the drawing comes out upside down on screen,
+/// where y points downwards, but areas and the results of clipping are the same
+pub fn parse_dxf (text: &str, tolerance: f32) -> Result<BTreeMap<String, Vec<Edge>>, ParseError> {
+    let pairs = pairs(text)?;
+    let mut rings: BTreeMap<String, Vec<Vec<Edge>>> = BTreeMap::new();
+    let mut section = "";
+    // the POLYLINE whose VERTEX entities are being read, if it is a 2D one
+    let mut polyline: Option<Polyline> = None;
+    for (start, group) in entities(&pairs) {
+        match start.value {
+            "SECTION" => section = group.iter().find(|p| p.code == 2).map_or("", |p| p.value),
+            "ENDSEC" => section = "",
+            _ if section != "ENTITIES" => {}
+            "LWPOLYLINE" => {
+                let mut p = Polyline::new(group)?;
+                for pair in group {
+                    match pair.code {
+                        10 => p.vertices.push((Vec2::new(pair.number()?, 0.0), 0.0)),
+                        20 => if let Some(v) = p.vertices.last_mut() { v.0.y = pair.number()? },
+                        42 => if let Some(v) = p.vertices.last_mut() { v.1 = pair.number()? },
+                        _ => {}
+                    }
+                }
+                p.finish(tolerance, &mut rings);
+            }
+            "POLYLINE" => {
+                let p = Polyline::new(group)?;
+                polyline = if p.flags & (POLYGON_MESH | POLYFACE_MESH) == 0 { Some(p) } else { None };
+            }
+            "VERTEX" => if let Some(p) = &mut polyline {
+                let flags = group.iter().find(|p| p.code == 70).map_or(Ok(0), Pair::integer)?;
+                if flags & (SPLINE_FRAME | POLYFACE_VERTEX) != 0 { continue }
+                let mut vertex = (Vec2::ZERO, 0.0);
+                for pair in group {
+                    match pair.code {
+                        10 => vertex.0.x = pair.number()?,
+                        20 => vertex.0.y = pair.number()?,
+                        42 => vertex.1 = pair.number()?,
+                        _ => {}
+                    }
+                }
+                p.vertices.push(vertex);
+            }
+            "SEQEND" => if let Some(p) = polyline.take() {
+                p.finish(tolerance, &mut rings);
+            }
+            _ => {}
+        }
+    }
+    Ok(rings.into_iter()
+        .map(|(layer, rings)| (layer, resolve_fill(&rings, FillRule::EvenOdd)))
+        .filter(|(_, polygon)| !polygon.is_empty())
+        .collect())
+}
+
+/// a minimal drawing with every ring of the polygon as a closed LWPOLYLINE on `layer`
+pub fn to_dxf (polygon: &[Edge], layer: &str) -> String {
+    let mut dxf = String::from("0\nSECTION\n2\nENTITIES\n");
+    for ring in split_rings(polygon) {
+        dxf += &format!("0\nLWPOLYLINE\n100\nAcDbEntity\n8\n{}\n100\nAcDbPolyline\n90\n{}\n70\n{}\n", layer, ring.len(), CLOSED);
+        for e in &ring {
+            dxf += &format!("10\n{}\n20\n{}\n", e.from.x, e.from.y);
+        }
+    }
+    dxf + "0\nENDSEC\n0\nEOF\n"
+}
+
+/// a group code and its value. `offset` is where the value starts
+struct Pair<'a> {
+    code: i32,
+    value: &'a str,
+    offset: usize
+}
+
+impl Pair<'_> {
+    fn number(&self) -> Result<f32, ParseError> {
+        self.value.parse::<f64>().map(|v| v as f32)
+            .map_err(|_| ParseError::new(format!("expected a number for group code {}", self.code), self.offset))
+    }
+
+    fn integer(&self) -> Result<i32, ParseError> {
+        self.value.parse::<i32>()
+            .map_err(|_| ParseError::new(format!("expected an integer for group code {}", self.code), self.offset))
+    }
+}
+
+fn pairs(text: &str) -> Result<Vec<Pair<'_>>, ParseError> {
+    let mut lines = vec![];
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        lines.push((line.trim(), offset + line.len() - line.trim_start().len()));
+        offset += line.len();
+    }
+    // a trailing newline leaves no line of its own
+    if lines.last().is_some_and(|(line, _)| line.is_empty()) {
+        lines.pop();
+    }
+    lines.chunks(2).map(|chunk| {
+        let (code, code_offset) = chunk[0];
+        let code = code.parse::<i32>().map_err(|_| ParseError::new("expected a group code", code_offset))?;
+        let &(value, offset) = chunk.get(1).ok_or_else(|| ParseError::new("group code without a value", text.len()))?;
+        Ok(Pair { code, value, offset })
+    }).collect()
+}
+
+/// every group code 0 starts an entity (or a section marker), which runs until the next one
+fn entities<'a, 'b>(pairs: &'b [Pair<'a>]) -> Vec<(&'b Pair<'a>, &'b [Pair<'a>])> {
+    let starts: Vec<usize> = (0..pairs.len()).filter(|i| pairs[*i].code == 0).collect();
+    starts.iter().enumerate().map(|(k, start)| {
+        let end = starts.get(k + 1).copied().unwrap_or(pairs.len());
+        (&pairs[*start], &pairs[(start + 1)..end])
+    }).collect()
+}
+
+/// vertices of a polyline with the bulge of the segment leaving each of them
+struct Polyline {
+    layer: String,
+    flags: i32,
+    vertices: Vec<(Vec2, f32)>
+}
+
+impl Polyline {
+    fn new(group: &[Pair]) -> Result<Polyline, ParseError> {
+        let layer = group.iter().find(|p| p.code == 8).map_or("0", |p| p.value).to_string();
+        let flags = group.iter().find(|p| p.code == 70).map_or(Ok(0), Pair::integer)?;
+        Ok(Polyline { layer, flags, vertices: vec![] })
+    }
+
+    /// adds the outline to its layer if the polyline is closed
+    fn finish(mut self, tolerance: f32, rings: &mut BTreeMap<String, Vec<Vec<Edge>>>) {
+        let ends_at_start = self.vertices.len() > 1 && self.vertices.first().map(|v| v.0) == self.vertices.last().map(|v| v.0);
+        if ends_at_start {
+            self.vertices.pop();
+        } else if self.flags & CLOSED == 0 {
+            return
+        }
+        let mut points = vec![];
+        for (i, (from, bulge)) in self.vertices.iter().enumerate() {
+            points.push(*from);
+            let to = self.vertices[(i + 1) % self.vertices.len()].0;
+            if *bulge != 0.0 {
+                flatten_bulge(&mut points, *from, to, *bulge, tolerance);
+            }
+        }
+        points.dedup();
+        if points.len() >= 3 {
+            rings.entry(self.layer).or_default().push(point_vec_to_edges(&points));
+        }
+    }
+}
+
+/// the points strictly between `from` and `to` on an arc with the given bulge,
+/// the tangent of a quarter of its angle. positive bulges turn counter-clockwise with y upwards
+fn flatten_bulge(out: &mut Vec<Vec2>, from: Vec2, to: Vec2, bulge: f32, tolerance: f32) {
+    let (from, to, bulge) = ((from.x as f64, from.y as f64), (to.x as f64, to.y as f64), bulge as f64);
+    let chord = (to.0 - from.0, to.1 - from.1);
+    let length = (chord.0 * chord.0 + chord.1 * chord.1).sqrt();
+    if length == 0.0 { return }
+    // the centre lies to the left of the chord for positive bulges
+    let d = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let centre = ((from.0 + to.0) / 2.0 - chord.1 * d, (from.1 + to.1) / 2.0 + chord.0 * d);
+    let radius = length * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+    let sweep = 4.0 * bulge.atan();
+    let start = (from.1 - centre.1).atan2(from.0 - centre.0);
+    let step = if (tolerance as f64) < radius { 2.0 * (1.0 - tolerance as f64 / radius).acos() } else { PI / 2.0 };
+    let n = (sweep.abs() / step.max(1e-3)).ceil().max(1.0) as usize;
+    for k in 1..n {
+        let angle = start + sweep * k as f64 / n as f64;
+        out.push(Vec2::new((centre.0 + radius * angle.cos()) as f32, (centre.1 + radius * angle.sin()) as f32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::area;
+    use crate::io::dxf::{parse_dxf, to_dxf};
+    use crate::io::ParseError;
+    use crate::polygon::split_rings;
+
+    /// a 10 by 10 plate with a hole of radius 2 made of two half circles, a triangle drawn
+    /// the old way on another layer, and an open polyline
+    const DRAWING: &str = "  0\r\nSECTION\r\n  2\r\nHEADER\r\n  9\r\n$ACADVER\r\n  1\r\nAC1015\r\n  0\r\nENDSEC
+0\nSECTION\n2\nBLOCKS\n0\nLWPOLYLINE\n8\nPLATE\n70\n1\n10\n0\n20\n0\n10\n1\n20\n0\n10\n1\n20\n1\n0\nENDSEC
+0\nSECTION\n2\nENTITIES
+0\nLWPOLYLINE\n8\nPLATE\n90\n4\n70\n1\n10\n0\n20\n0\n10\n10\n20\n0\n10\n10\n20\n10\n10\n0\n20\n10
+0\nLWPOLYLINE\n8\nPLATE\n90\n2\n70\n1\n10\n3\n20\n5\n42\n1\n10\n7\n20\n5\n42\n1
+0\nPOLYLINE\n8\nFRAME\n66\n1\n70\n0
+0\nVERTEX\n8\nFRAME\n10\n20\n20\n0\n0\nVERTEX\n8\nFRAME\n10\n24\n20\n0\n0\nVERTEX\n8\nFRAME\n10\n20\n20\n3
+0\nVERTEX\n8\nFRAME\n10\n20\n20\n0\n0\nSEQEND
+0\nLWPOLYLINE\n8\nOPEN\n70\n0\n10\n0\n20\n0\n10\n1\n20\n0\n10\n1\n20\n1
+0\nENDSEC\n0\nEOF\n";
+
+    #[test]
+    fn test_parse() {
+        let layers = parse_dxf(DRAWING, 0.001).unwrap();
+        assert_eq!(layers.keys().collect::<Vec<_>>(), vec!["FRAME", "PLATE"]);
+        assert_eq!(split_rings(&layers["PLATE"]).len(), 2);
+        let plate = 100.0 - 4.0 * std::f32::consts::PI;
+        assert!((area(&layers["PLATE"]) - plate).abs() < 0.02, "plate area {}", area(&layers["PLATE"]));
+        assert_eq!(area(&layers["FRAME"]), 6.0);
+        let coarse = parse_dxf(DRAWING, 0.5).unwrap();
+        assert!(coarse["PLATE"].len() < layers["PLATE"].len());
+        // going counter-clockwise from (0, 0) to (2, 0) passes below the chord
+        let half_disc = "0\nSECTION\n2\nENTITIES\n0\nLWPOLYLINE\n70\n1\n10\n0\n20\n0\n42\n1\n10\n2\n20\n0\n0\nENDSEC\n";
+        let layers = parse_dxf(half_disc, 0.01).unwrap();
+        assert!(layers["0"].iter().all(|e| e.from.y <= 0.0) && layers["0"].iter().any(|e| e.from.y < -0.9));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let layers = parse_dxf(DRAWING, 0.01).unwrap();
+        let written = to_dxf(&layers["PLATE"], "RESULT");
+        let read_back = parse_dxf(&written, 0.01).unwrap();
+        assert_eq!(read_back.keys().collect::<Vec<_>>(), vec!["RESULT"]);
+        assert_eq!(read_back["RESULT"].len(), layers["PLATE"].len());
+        assert!((area(&read_back["RESULT"]) - area(&layers["PLATE"])).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_dxf("0\nSECTION\nx\nENTITIES\n", 0.1).unwrap_err(), ParseError::new("expected a group code", 10));
+        assert_eq!(parse_dxf("0\nSECTION\n2\nENTITIES\n0\nLWPOLYLINE\n10\none\n", 0.1).unwrap_err(),
+                   ParseError::new("expected a number for group code 10", 37));
+        assert_eq!(parse_dxf("0\nSECTION\n2", 0.1).unwrap_err(), ParseError::new("group code without a value", 11));
+    }
+}
@@ -0,0 +1,250 @@
+use serde_json::{json, Map, Value};
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::{boolean_op, BooleanOp};
+use crate::edge::Edge;
+use crate::io::{polygon_from_rings, Geometry, ParseError};
+use crate::polygon::{reverse_ring, split_rings};
+
+/// a GeoJSON feature. `geometry` is `None` for features without a location
+#[derive(Clone, Debug, PartialEq)]
+pub struct Feature {
+    pub geometry: Option<Geometry>,
+    pub properties: Map<String, Value>,
+    pub id: Option<Value>
+}
+
+impl Feature {
+    pub fn new(geometry: Geometry) -> Self {
+        Feature { geometry: Some(geometry), properties: Map::new(), id: None }
+    }
+}
+
+/// reads a FeatureCollection, a single Feature or a bare Polygon, MultiPolygon or LineString.
+/// RFC 7946 draws outer rings counter-clockwise with y upwards, which is clockwise in the
+/// crate's frame, so rings are reoriented like in `parse_wkt`: the first ring of a polygon
+/// becomes the outer ring whatever way it was drawn
+pub fn parse_geojson (text: &str) -> Result<Vec<Feature>, ParseError> {
+    let value: Value = serde_json::from_str(text).map_err(|e| ParseError::from_json(text, &e))?;
+    match member_str(&value, "type", "")? {
+        "FeatureCollection" => {
+            let features = value.get("features").and_then(Value::as_array)
+                .ok_or_else(|| ParseError::without_offset("features: expected an array"))?;
+            features.iter().enumerate()
+                .map(|(i, f)| feature(f, &format!("features[{}]", i)))
+                .collect()
+        }
+        "Feature" => Ok(vec![feature(&value, "")?]),
+        _ => Ok(vec![Feature::new(geometry(&value, "")?)])
+    }
+}
+
+/// writes a FeatureCollection. rings are closed and turned the way RFC 7946 expects them:
+/// outer rings counter-clockwise and holes clockwise with y upwards
+pub fn to_geojson (features: &[Feature]) -> String {
+    let features: Vec<Value> = features.iter().map(|f| {
+        let mut value = json!({
+            "type": "Feature",
+            "geometry": f.geometry.as_ref().map_or(Value::Null, geometry_value),
+            "properties": f.properties
+        });
+        if let Some(id) = &f.id {
+            value["id"] = id.clone();
+        }
+        value
+    }).collect();
+    serde_json::to_string(&json!({ "type": "FeatureCollection", "features": features })).unwrap()
+}
+
+/// applies `op` between every feature and `clipping`, keeping the id and properties of each feature.
+/// features left empty, line strings and features without a geometry are dropped
+pub fn clip_features (features: &[Feature], clipping: &[Edge], op: BooleanOp) -> Vec<Feature> {
+    features.iter().filter_map(|f| {
+        let subject = f.geometry.as_ref()?.to_polygon();
+        if subject.is_empty() { return None }
+        let result = boolean_op(&subject, clipping, op);
+        if result.is_empty() { return None }
+        Some(Feature { geometry: Some(Geometry::from_polygon(&result)), ..f.clone() })
+    }).collect()
+}
+
+fn error(path: &str, message: &str) -> ParseError {
+    if path.is_empty() {
+        ParseError::without_offset(message)
+    } else {
+        ParseError::without_offset(format!("{}: {}", path, message))
+    }
+}
+
+fn join(path: &str, member: &str) -> String {
+    if path.is_empty() { member.to_string() } else { format!("{}.{}", path, member) }
+}
+
+fn member_str<'a>(value: &'a Value, member: &str, path: &str) -> Result<&'a str, ParseError> {
+    value.get(member).and_then(Value::as_str)
+        .ok_or_else(|| error(&join(path, member), "expected a string"))
+}
+
+fn feature(value: &Value, path: &str) -> Result<Feature, ParseError> {
+    if member_str(value, "type", path)? != "Feature" {
+        return Err(error(&join(path, "type"), "expected \"Feature\""))
+    }
+    let geometry = match value.get("geometry") {
+        None | Some(Value::Null) => None,
+        Some(g) => Some(geometry(g, &join(path, "geometry"))?)
+    };
+    let properties = match value.get("properties") {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(properties)) => properties.clone(),
+        Some(_) => return Err(error(&join(path, "properties"), "expected an object"))
+    };
+    Ok(Feature { geometry, properties, id: value.get("id").cloned() })
+}
+
+fn geometry(value: &Value, path: &str) -> Result<Geometry, ParseError> {
+    let coordinates_path = join(path, "coordinates");
+    let coordinates = value.get("coordinates").unwrap_or(&Value::Null);
+    match member_str(value, "type", path)? {
+        "Polygon" => Ok(Geometry::Polygon(polygon(coordinates, &coordinates_path)?)),
+        "MultiPolygon" => Ok(Geometry::MultiPolygon(array(coordinates, &coordinates_path)?.iter().enumerate()
+            .map(|(i, p)| polygon(p, &format!("{}[{}]", coordinates_path, i)))
+            .collect::<Result<_, _>>()?)),
+        "LineString" => Ok(Geometry::LineString(positions(coordinates, &coordinates_path)?)),
+        other => Err(error(&join(path, "type"), &format!("unsupported geometry type {}", other)))
+    }
+}
+
+fn array<'a>(value: &'a Value, path: &str) -> Result<&'a Vec<Value>, ParseError> {
+    value.as_array().ok_or_else(|| error(path, "expected an array"))
+}
+
+/// x and y, any further ordinates are dropped
+fn positions(value: &Value, path: &str) -> Result<Vec<Vec2>, ParseError> {
+    array(value, path)?.iter().enumerate().map(|(i, position)| {
+        let ordinates: Option<Vec<f64>> = position.as_array()
+            .map(|o| o.iter().map(Value::as_f64).collect())
+            .unwrap_or(None);
+        match ordinates.as_deref() {
+            Some([x, y, ..]) => Ok(Vec2::new(*x as f32, *y as f32)),
+            _ => Err(error(&format!("{}[{}]", path, i), "expected a position of at least 2 numbers"))
+        }
+    }).collect()
+}
+
+fn polygon(value: &Value, path: &str) -> Result<Vec<Edge>, ParseError> {
+    let mut rings = vec![];
+    for (i, ring) in array(value, path)?.iter().enumerate() {
+        let ring_path = format!("{}[{}]", path, i);
+        let mut ring = positions(ring, &ring_path)?;
+        if ring.len() < 4 {
+            return Err(error(&ring_path, "a ring needs at least 4 positions"))
+        }
+        if ring.first() != ring.last() {
+            return Err(error(&ring_path, "ring is not closed"))
+        }
+        ring.pop();
+        rings.push(ring);
+    }
+    Ok(polygon_from_rings(&rings))
+}
+
+/// the shortest decimal that reads back as the same f32, rather than its exact f64 value
+fn number(v: f32) -> Value {
+    Value::from(v.to_string().parse::<f64>().unwrap())
+}
+
+fn position(p: Vec2) -> Value {
+    json!([number(p.x), number(p.y)])
+}
+
+fn polygon_value(polygon: &[Edge]) -> Value {
+    split_rings(polygon).iter().map(|ring| {
+        let ring = reverse_ring(ring);
+        ring.iter().map(|e| e.from).chain([ring[0].from]).map(position).collect::<Value>()
+    }).collect()
+}
+
+fn geometry_value(geometry: &Geometry) -> Value {
+    match geometry {
+        Geometry::Polygon(polygon) => json!({ "type": "Polygon", "coordinates": polygon_value(polygon) }),
+        Geometry::MultiPolygon(polygons) => json!({
+            "type": "MultiPolygon",
+            "coordinates": polygons.iter().map(|p| polygon_value(p)).collect::<Vec<_>>()
+        }),
+        Geometry::LineString(points) => json!({
+            "type": "LineString",
+            "coordinates": points.iter().map(|p| position(*p)).collect::<Vec<_>>()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::boolean::BooleanOp;
+    use crate::io::geojson::{clip_features, parse_geojson, to_geojson};
+    use crate::io::wkt::parse_wkt;
+    use crate::io::{Geometry, ParseError};
+
+    const FIELDS: &str = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            { "type": "Feature", "id": 7, "properties": { "crop": "wheat" },
+              "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [4, 0], [4, 4], [0, 4], [0, 0]]] } },
+            { "type": "Feature", "properties": { "crop": "barley" },
+              "geometry": { "type": "MultiPolygon", "coordinates": [[[[10, 0], [11, 0], [11, 1], [10, 0]]]] } },
+            { "type": "Feature", "properties": null, "geometry": null }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse() {
+        let features = parse_geojson(FIELDS).unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0].geometry, Some(parse_wkt("POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0))").unwrap()));
+        assert_eq!(features[0].id, Some(json!(7)));
+        assert_eq!(features[1].properties["crop"], "barley");
+        assert_eq!(features[2].geometry, None);
+        let bare = parse_geojson(r#"{ "type": "LineString", "coordinates": [[1, 2, 3], [4.5, 6]] }"#).unwrap();
+        assert_eq!(bare[0].geometry, Some(parse_wkt("LINESTRING (1 2, 4.5 6)").unwrap()));
+    }
+
+    #[test]
+    fn test_winding() {
+        let features = parse_geojson(FIELDS).unwrap();
+        let written: Value = serde_json::from_str(&to_geojson(&features)).unwrap();
+        let ring = written["features"][0]["geometry"]["coordinates"][0].as_array().unwrap();
+        assert_eq!(ring.first(), ring.last(), "rings should be closed");
+        let area: f64 = ring.windows(2).map(|w| {
+            let (a, b) = (&w[0], &w[1]);
+            a[0].as_f64().unwrap() * b[1].as_f64().unwrap() - b[0].as_f64().unwrap() * a[1].as_f64().unwrap()
+        }).sum();
+        assert!(area > 0.0, "outer rings should be counter-clockwise with y upwards");
+        assert_eq!(written["features"][0]["id"], 7);
+        assert_eq!(parse_geojson(&to_geojson(&features)).unwrap(), features);
+    }
+
+    #[test]
+    fn test_clip() {
+        let features = parse_geojson(FIELDS).unwrap();
+        let clipping = parse_wkt("POLYGON ((2 2, 6 2, 6 6, 2 6, 2 2))").unwrap().to_polygon();
+        let clipped = clip_features(&features, &clipping, BooleanOp::Intersection);
+        assert_eq!(clipped.len(), 1, "features outside the clipping polygon should be dropped");
+        assert_eq!(clipped[0].properties["crop"], "wheat");
+        assert_eq!(clipped[0].id, Some(json!(7)));
+        let Some(Geometry::Polygon(polygon)) = &clipped[0].geometry else { panic!("expected a polygon") };
+        assert_eq!(polygon.len(), 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_geojson("{\n  \"type\": x }").unwrap_err().offset, Some(12));
+        assert_eq!(parse_geojson(r#"{ "type": "Point", "coordinates": [1, 2] }"#).unwrap_err(),
+                   ParseError::without_offset("type: unsupported geometry type Point"));
+        let open = r#"{ "type": "FeatureCollection", "features": [{ "type": "Feature", "properties": {},
+            "geometry": { "type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 1]]] } }] }"#;
+        assert_eq!(parse_geojson(open).unwrap_err(),
+                   ParseError::without_offset("features[0].geometry.coordinates[0]: ring is not closed"));
+    }
+}
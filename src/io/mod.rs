@@ -0,0 +1,156 @@
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::union_all;
+use crate::edge::Edge;
+use crate::io::geojson::{parse_geojson, to_geojson, Feature};
+use crate::polygon::{group_rings, is_polygon_clockwise, point_vec_to_edges, reverse_ring, RingGroup};
+
+pub mod dxf;
+pub mod geojson;
+pub mod scene;
+pub mod svg;
+pub mod wkb;
+pub mod wkt;
+
+/// geometry read from or written to a file.
+/// polygons are stored the way the rest of the crate expects them:
+/// consecutive rings of edges, the outer ring counter-clockwise and holes clockwise (with y downwards)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Geometry {
+    /// an outer ring followed by its holes
+    Polygon(Vec<Edge>),
+    MultiPolygon(Vec<Vec<Edge>>),
+    LineString(Vec<Vec2>)
+}
+
+impl Geometry {
+    /// all the polygons as one edge list, which can be passed to `clip_polygon`.
+    /// line strings have no area and are left out
+    pub fn to_polygon(&self) -> Vec<Edge> {
+        match self {
+            Geometry::Polygon(polygon) => polygon.clone(),
+            Geometry::MultiPolygon(polygons) => polygons.concat(),
+            Geometry::LineString(_) => vec![]
+        }
+    }
+
+    /// a single polygon if there is at most one outer ring, otherwise a multi polygon
+    pub fn from_polygon(polygon: &[Edge]) -> Geometry {
+        let mut polygons: Vec<Vec<Edge>> = group_rings(polygon).into_iter()
+            .map(|RingGroup { outer, holes }| [vec![outer], holes].concat().concat())
+            .collect();
+        if polygons.len() == 1 {
+            Geometry::Polygon(polygons.pop().unwrap())
+        } else {
+            Geometry::MultiPolygon(polygons)
+        }
+    }
+}
+
+/// the file formats polygons can be read from and written to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Wkt,
+    /// binary, or hexadecimal text when reading
+    Wkb,
+    GeoJson,
+    Svg,
+    Dxf
+}
+
+impl Format {
+    /// `wkt`, `wkb`, `geojson` (or `json`), `svg` or `dxf`, in any case
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name.to_ascii_lowercase().as_str() {
+            "wkt" => Some(Format::Wkt),
+            "wkb" => Some(Format::Wkb),
+            "geojson" | "json" => Some(Format::GeoJson),
+            "svg" => Some(Format::Svg),
+            "dxf" => Some(Format::Dxf),
+            _ => None
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Format> {
+        Format::from_name(path.extension()?.to_str()?)
+    }
+}
+
+/// reads all the polygons in a file as one polygon, merging the parts that overlap:
+/// the features of a GeoJSON file, the shapes of an SVG file and the layers of a DXF file.
+/// `tolerance` is how far flattened curves may stray from the outline
+pub fn read_polygon(data: &[u8], format: Format, tolerance: f32) -> Result<Vec<Edge>, ParseError> {
+    if format == Format::Wkb && !data.iter().all(|b| b.is_ascii_hexdigit() || b.is_ascii_whitespace()) {
+        return Ok(wkb::parse_wkb(data)?.to_polygon())
+    }
+    let text = std::str::from_utf8(data)
+        .map_err(|e| ParseError::new("the file is not valid UTF-8", e.valid_up_to()))?;
+    Ok(match format {
+        Format::Wkt => wkt::parse_wkt(text)?.to_polygon(),
+        Format::Wkb => wkb::parse_wkb_hex(text)?.to_polygon(),
+        Format::GeoJson => union_all(&parse_geojson(text)?.iter()
+            .filter_map(|f| f.geometry.as_ref().map(Geometry::to_polygon))
+            .collect::<Vec<_>>()),
+        Format::Svg => union_all(&svg::parse_svg(text, tolerance)?),
+        Format::Dxf => union_all(&dxf::parse_dxf(text, tolerance)?.into_values().collect::<Vec<_>>())
+    })
+}
+
+/// the polygon as the contents of a file. DXF outlines go on layer 0
+pub fn write_polygon(polygon: &[Edge], format: Format) -> Vec<u8> {
+    let geometry = Geometry::from_polygon(polygon);
+    match format {
+        Format::Wkt => (wkt::to_wkt(&geometry) + "\n").into_bytes(),
+        Format::Wkb => wkb::to_wkb(&geometry),
+        Format::GeoJson => to_geojson(&[Feature::new(geometry)]).into_bytes(),
+        Format::Svg => svg::to_svg(&[polygon.to_vec()]).into_bytes(),
+        Format::Dxf => dxf::to_dxf(polygon, "0").into_bytes()
+    }
+}
+
+/// where and why reading a file failed. `offset` counts bytes from the start of the input,
+/// and is missing for errors that are not tied to one place in the text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub offset: Option<usize>
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>, offset: usize) -> Self {
+        ParseError { message: message.into(), offset: Some(offset) }
+    }
+
+    pub fn without_offset(message: impl Into<String>) -> Self {
+        ParseError { message: message.into(), offset: None }
+    }
+
+    /// serde reports lines and columns starting at 1
+    fn from_json(text: &str, e: &serde_json::Error) -> Self {
+        let line_start: usize = text.split_inclusive('\n').take(e.line().saturating_sub(1)).map(str::len).sum();
+        ParseError::new(e.to_string(), (line_start + e.column().saturating_sub(1)).min(text.len()))
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{} (at byte {})", self.message, offset),
+            None => write!(f, "{}", self.message)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// builds a polygon from rings whose first ring is the outer one, whatever way they were drawn.
+/// the outer ring is turned counter-clockwise and the holes clockwise
+pub fn polygon_from_rings(rings: &[Vec<Vec2>]) -> Vec<Edge> {
+    rings.iter().enumerate().flat_map(|(i, points)| {
+        let ring = point_vec_to_edges(points);
+        if is_polygon_clockwise(&ring) == (i == 0) { reverse_ring(&ring) } else { ring }
+    }).collect()
+}
@@ -0,0 +1,144 @@
+use serde_json::Value;
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::io::ParseError;
+use crate::polygon::{point_vec_to_edges, split_rings, Rings};
+
+/// the polygons drawn in the viewer. the subject and clipping polygons are each a list of
+/// independent polygons, which may overlap, and keeps its rings apart as they were drawn
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Scene {
+    pub subject: Vec<Rings>,
+    pub clipping: Vec<Rings>,
+    pub result: Vec<Edge>
+}
+
+fn rings_json<'a> (rings: impl Iterator<Item=&'a [Edge]>, indent: &str) -> String {
+    let rings: Vec<String> = rings.map(|ring| {
+        let points: Vec<String> = ring.iter().map(|e| format!("[{}, {}]", e.from.x, e.from.y)).collect();
+        format!("{}  [{}]", indent, points.join(", "))
+    }).collect();
+    if rings.is_empty() { "[]".to_string() } else { format!("[\n{}\n{}]", rings.join(",\n"), indent) }
+}
+
+/// a JSON object with a list of polygons for the subject and clipping, each a list of rings,
+/// and a list of rings for the result. a ring is a list of `[x, y]` points, not closed.
+/// unlike the other formats, rings are stored exactly as they are in memory: in the same order,
+/// starting at the same vertex and turning the same way, so that a scene reproduces the same clip
+pub fn to_scene_json (scene: &Scene) -> String {
+    let polygons = |role: &str, polygons: &[Rings]| {
+        let polygons: Vec<String> = polygons.iter().map(|polygon| format!("    {}", rings_json(polygon.rings(), "    "))).collect();
+        if polygons.is_empty() {
+            format!("  \"{}\": []", role)
+        } else {
+            format!("  \"{}\": [\n{}\n  ]", role, polygons.join(",\n"))
+        }
+    };
+    format!("{{\n{},\n{},\n  \"result\": {}\n}}\n",
+            polygons("subject", &scene.subject), polygons("clipping", &scene.clipping), rings_json(split_rings(&scene.result).iter().map(Vec::as_slice), "  "))
+}
+
+/// `path` names the list in errors, like `subject[1]`
+fn parse_rings (value: &Value, path: &str) -> Result<Rings, ParseError> {
+    let rings = value.as_array()
+        .ok_or_else(|| ParseError::without_offset(format!("{}: expected a list of rings", path)))?;
+    let mut polygon = Rings::default();
+    for (i, ring) in rings.iter().enumerate() {
+        let error = |message: &str| ParseError::without_offset(format!("{}[{}]: {}", path, i, message));
+        let points: Option<Vec<Vec2>> = ring.as_array().and_then(|points| points.iter().map(|p| match p.as_array()?.as_slice() {
+            [x, y] => Some(Vec2::new(x.as_f64()? as f32, y.as_f64()? as f32)),
+            _ => None
+        }).collect());
+        let points = points.ok_or_else(|| error("expected a list of [x, y] points"))?;
+        if points.len() < 3 {
+            return Err(error("a ring needs at least 3 points"))
+        }
+        polygon.push_ring(&point_vec_to_edges(&points));
+    }
+    Ok(polygon)
+}
+
+/// reads a scene written by `to_scene_json`. missing polygons are empty. a subject or clipping
+/// that is a list of rings, as scenes used to be written, is one polygon
+pub fn parse_scene (text: &str) -> Result<Scene, ParseError> {
+    let value: Value = serde_json::from_str(text).map_err(|e| ParseError::from_json(text, &e))?;
+    if !value.is_object() {
+        return Err(ParseError::without_offset("a scene must be a JSON object"))
+    }
+    let polygons = |role: &str| {
+        let Some(polygons) = value.get(role) else { return Ok(vec![]) };
+        let is_ring = |v: &Value| v.get(0).and_then(|p| p.get(0)).is_some_and(Value::is_number);
+        if polygons.get(0).is_some_and(is_ring) {
+            return Ok(vec![parse_rings(polygons, role)?])
+        }
+        polygons.as_array()
+            .ok_or_else(|| ParseError::without_offset(format!("{}: expected a list of polygons", role)))?
+            .iter().enumerate()
+            .map(|(i, polygon)| parse_rings(polygon, &format!("{}[{}]", role, i)))
+            .collect::<Result<Vec<_>, ParseError>>()
+    };
+    let subject = polygons("subject")?;
+    let clipping = polygons("clipping")?;
+    let result = match value.get("result") {
+        Some(result) => parse_rings(result, "result")?.edges,
+        None => vec![]
+    };
+    Ok(Scene { subject, clipping, result })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::ring;
+    use crate::io::scene::{parse_scene, to_scene_json, Scene};
+    use crate::io::ParseError;
+    use crate::polygon::Rings;
+
+    #[test]
+    fn test_round_trip() {
+        // a hole outside its shell and a clockwise outer ring are kept as they are
+        let square = ring(&[(1.0, 1.0), (5.0, 1.0), (5.0, 5.0), (1.0, 5.0)]);
+        let scene = Scene {
+            subject: vec![Rings::new([ring(&[(0.5, 0.1), (0.0, 4.0), (4.0, 4.0)]), ring(&[(10.0, 10.0), (11.0, 10.0), (11.0, 11.0)])].concat())],
+            // overlapping polygons stay apart
+            clipping: vec![Rings::new(square.clone()), Rings::new(ring(&[(2.0, 2.0), (6.0, 2.0), (6.0, 6.0)]))],
+            result: square.clone()
+        };
+        let json = to_scene_json(&scene);
+        assert!(json.contains("\"subject\": [\n    [\n      [[0.5, 0.1], [0, 4], [4, 4]],\n"));
+        assert!(json.contains("\"result\": [\n    [[1, 1], [5, 1], [5, 5], [1, 5]]\n  ]\n}"));
+        assert_eq!(parse_scene(&json).unwrap(), scene);
+        assert_eq!(parse_scene("{}").unwrap(), Scene::default());
+        assert_eq!(parse_scene("{\"subject\": [], \"result\": []}").unwrap(), Scene::default());
+        // the older format, with a list of rings for one polygon
+        let old = parse_scene("{\"subject\": [[[1, 1], [5, 1], [5, 5], [1, 5]], [[2, 2], [2, 3], [3, 3]]]}").unwrap();
+        assert_eq!(old.subject.len(), 1);
+        assert_eq!(old.subject[0].edges.len(), 7);
+        assert_eq!(old.subject[0].starts, vec![0, 4]);
+    }
+
+    #[test]
+    fn test_repeated_vertex() {
+        // a ring that passes through the same point twice is still one ring
+        let mut polygon = Rings::default();
+        polygon.push_ring(&ring(&[(0.0, 0.0), (0.0, 10.0), (0.0, 0.0), (10.0, 0.0)]));
+        let scene = Scene { subject: vec![polygon], ..Scene::default() };
+        let json = to_scene_json(&scene);
+        assert!(json.contains("[[0, 0], [0, 10], [0, 0], [10, 0]]"));
+        assert_eq!(parse_scene(&json).unwrap(), scene);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_scene("{\"subject\": [[[[0, 0], [1, 0]]]]}").unwrap_err(),
+                   ParseError::without_offset("subject[0][0]: a ring needs at least 3 points"));
+        assert_eq!(parse_scene("{\"clipping\": [[[[0, 0, 1]]]]}").unwrap_err(),
+                   ParseError::without_offset("clipping[0][0]: expected a list of [x, y] points"));
+        assert_eq!(parse_scene("{\"clipping\": [[[0, 0], [1, 0]]]}").unwrap_err(),
+                   ParseError::without_offset("clipping[0]: a ring needs at least 3 points"));
+        assert_eq!(parse_scene("{\"result\": [[[0, 0], [1, 0]]]}").unwrap_err(),
+                   ParseError::without_offset("result[0]: a ring needs at least 3 points"));
+        assert_eq!(parse_scene("[]").unwrap_err(), ParseError::without_offset("a scene must be a JSON object"));
+        assert_eq!(parse_scene("{\"result\": }").unwrap_err().offset, Some(11));
+    }
+}
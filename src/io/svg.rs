@@ -0,0 +1,642 @@
+use std::f64::consts::PI;
+
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::{resolve_fill, FillRule};
+use crate::edge::Edge;
+use crate::io::ParseError;
+use crate::polygon::{point_vec_to_edges, split_rings};
+use crate::vec::inner_product;
+
+/// deepest subdivision of a bezier curve, in case the tolerance cannot be met
+const MAX_DEPTH: u32 = 16;
+
+/// an affine transform `[a, b, c, d, e, f]`, mapping (x, y) to (ax + cy + e, bx + dy + f) like SVG does
+type Transform = [f32; 6];
+
+const IDENTITY: Transform = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// reads every `<polygon>`, `<polyline>`, `<rect>` and `<path>` into a polygon of its own,
+/// in document order. curves and rounded corners are flattened so that they stay within
+/// `tolerance` of the true outline, and `transform` attributes are applied.
+/// the area each element fills under its `fill-rule` is returned as a valid polygon:
+/// SVG is y-down like the crate, so outer rings come out counter-clockwise on screen.
+/// shapes inside `<defs>`, `<clipPath>`, `<mask>` and the like are not drawn, and are skipped
+pub fn parse_svg (text: &str, tolerance: f32) -> Result<Vec<Vec<Edge>>, ParseError> {
+    let mut scanner = Scanner { text, pos: 0 };
+    let mut stack: Vec<Style> = vec![];
+    let mut polygons = vec![];
+    while let Some(tag) = scanner.next_tag()? {
+        if tag.closing {
+            stack.pop();
+            continue
+        }
+        let parent = stack.last().copied().unwrap_or(Style { fill_rule: FillRule::NonZero, transform: IDENTITY, hidden: false });
+        let style = parent.apply(&tag)?;
+        if !tag.self_closing {
+            stack.push(style);
+        }
+        if style.hidden { continue }
+        let scale = (style.transform[0] * style.transform[3] - style.transform[1] * style.transform[2]).abs().sqrt();
+        let local_tolerance = if scale > 0.0 { tolerance / scale } else { tolerance };
+        let rings = match tag.name {
+            "polygon" | "polyline" => {
+                let (points, offset) = tag.attribute("points").unwrap_or(("", tag.offset));
+                vec![point_list(points, offset)?]
+            }
+            "rect" => rect_rings(&tag, local_tolerance)?,
+            "path" => {
+                let (d, offset) = tag.attribute("d").unwrap_or(("", tag.offset));
+                path_rings(d, offset, local_tolerance)?
+            }
+            _ => continue
+        };
+        let rings: Vec<Vec<Edge>> = rings.into_iter()
+            .map(|ring| ring.into_iter().map(|p| transform_point(&style.transform, p)).collect::<Vec<_>>())
+            .filter_map(clean_ring)
+            .map(|ring| point_vec_to_edges(&ring))
+            .collect();
+        let polygon = resolve_fill(&rings, style.fill_rule);
+        if !polygon.is_empty() {
+            polygons.push(polygon);
+        }
+    }
+    Ok(polygons)
+}
+
+/// a standalone SVG document with one compound `<path>` per polygon.
+/// the view box fits the polygons, and holes are cut out using the even-odd rule
+pub fn to_svg (polygons: &[Vec<Edge>]) -> String {
+    let points: Vec<Vec2> = polygons.iter().flatten().map(|e| e.from).collect();
+    let (min, max) = points.iter().fold(
+        (Vec2::new(f32::MAX, f32::MAX), Vec2::new(f32::MIN, f32::MIN)),
+        |(min, max), p| (Vec2::new(min.x.min(p.x), min.y.min(p.y)), Vec2::new(max.x.max(p.x), max.y.max(p.y)))
+    );
+    let (min, size) = if points.is_empty() { (Vec2::ZERO, Vec2::ZERO) } else { (min, max - min) };
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+        min.x, min.y, size.x, size.y, size.x, size.y
+    );
+    for polygon in polygons.iter().filter(|p| !p.is_empty()) {
+        svg += &format!("  <path fill=\"cyan\" stroke=\"blue\" fill-rule=\"evenodd\" d=\"{}\"/>\n", path_data(polygon));
+    }
+    svg + "</svg>\n"
+}
+
+/// path data drawing every ring of the polygon as a closed subpath
+pub fn path_data (polygon: &[Edge]) -> String {
+    split_rings(polygon).iter().map(|ring| {
+        let points: Vec<String> = ring.iter().map(|e| format!("{} {}", e.from.x, e.from.y)).collect();
+        format!("M {} Z", points.join(" L "))
+    }).collect::<Vec<_>>().join(" ")
+}
+
+/// what an element inherits from its parents
+#[derive(Copy, Clone)]
+struct Style {
+    fill_rule: FillRule,
+    transform: Transform,
+    hidden: bool
+}
+
+impl Style {
+    fn apply(self, tag: &Tag) -> Result<Style, ParseError> {
+        let fill_rule = match tag.property("fill-rule") {
+            Some(("evenodd", _)) => FillRule::EvenOdd,
+            Some(("nonzero", _)) => FillRule::NonZero,
+            _ => self.fill_rule
+        };
+        let transform = match tag.attribute("transform") {
+            Some((text, offset)) => compose(&self.transform, &parse_transform(text, offset)?),
+            None => self.transform
+        };
+        let hidden = self.hidden
+            || matches!(tag.name, "defs" | "clipPath" | "mask" | "symbol" | "pattern" | "marker")
+            || tag.property("display").is_some_and(|(v, _)| v == "none");
+        Ok(Style { fill_rule, transform, hidden })
+    }
+}
+
+fn compose(outer: &Transform, inner: &Transform) -> Transform {
+    let [a, b, c, d, e, f] = *outer;
+    let [a2, b2, c2, d2, e2, f2] = *inner;
+    [
+        a * a2 + c * b2, b * a2 + d * b2,
+        a * c2 + c * d2, b * c2 + d * d2,
+        a * e2 + c * f2 + e, b * e2 + d * f2 + f
+    ]
+}
+
+fn transform_point(t: &Transform, p: Vec2) -> Vec2 {
+    Vec2::new(t[0] * p.x + t[2] * p.y + t[4], t[1] * p.x + t[3] * p.y + t[5])
+}
+
+/// a list like `translate(10 20) rotate(45)`, applied right to left
+fn parse_transform(text: &str, offset: usize) -> Result<Transform, ParseError> {
+    let mut transform = IDENTITY;
+    let mut s = Numbers { text, pos: 0, offset };
+    loop {
+        s.skip_separators();
+        if s.rest().is_empty() { break }
+        let start = s.pos;
+        let len = s.rest().find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(s.rest().len());
+        let name = &s.rest()[..len];
+        s.pos += len;
+        s.skip_separators();
+        if !s.rest().starts_with('(') { return Err(s.error("expected '('")) }
+        s.pos += 1;
+        let mut args = vec![];
+        loop {
+            s.skip_separators();
+            if s.rest().starts_with(')') { s.pos += 1; break }
+            args.push(s.number()?);
+        }
+        let t = match (name, args.as_slice()) {
+            ("matrix", [a, b, c, d, e, f]) => [*a, *b, *c, *d, *e, *f],
+            ("translate", [x]) => [1.0, 0.0, 0.0, 1.0, *x, 0.0],
+            ("translate", [x, y]) => [1.0, 0.0, 0.0, 1.0, *x, *y],
+            ("scale", [s]) => [*s, 0.0, 0.0, *s, 0.0, 0.0],
+            ("scale", [x, y]) => [*x, 0.0, 0.0, *y, 0.0, 0.0],
+            ("rotate", [angle, rest @ ..]) if rest.is_empty() || rest.len() == 2 => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let rotation = [cos, sin, -sin, cos, 0.0, 0.0];
+                match rest {
+                    [cx, cy] => compose(&compose(&[1.0, 0.0, 0.0, 1.0, *cx, *cy], &rotation), &[1.0, 0.0, 0.0, 1.0, -cx, -cy]),
+                    _ => rotation
+                }
+            }
+            ("skewX", [angle]) => [1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0],
+            ("skewY", [angle]) => [1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0],
+            _ => return Err(ParseError::new(format!("invalid transform {}", name), offset + start))
+        };
+        transform = compose(&transform, &t);
+    }
+    Ok(transform)
+}
+
+/// drops repeated points, including a last point repeating the first. None if less than 3 are left
+fn clean_ring(mut ring: Vec<Vec2>) -> Option<Vec<Vec2>> {
+    ring.dedup();
+    while ring.len() > 1 && ring.first() == ring.last() {
+        ring.pop();
+    }
+    if ring.len() >= 3 { Some(ring) } else { None }
+}
+
+fn point_list(text: &str, offset: usize) -> Result<Vec<Vec2>, ParseError> {
+    let mut s = Numbers { text, pos: 0, offset };
+    let mut points = vec![];
+    loop {
+        s.skip_separators();
+        if s.rest().is_empty() { break }
+        points.push(s.point()?);
+    }
+    Ok(points)
+}
+
+/// a length in user units. `px` is the only unit allowed
+fn length(tag: &Tag, name: &str) -> Result<f32, ParseError> {
+    let Some((text, offset)) = tag.attribute(name) else { return Ok(0.0) };
+    let mut s = Numbers { text, pos: 0, offset };
+    let value = s.number()?;
+    if !matches!(s.rest().trim(), "" | "px") {
+        return Err(s.error("unsupported unit"))
+    }
+    Ok(value)
+}
+
+fn rect_rings(tag: &Tag, tolerance: f32) -> Result<Vec<Vec<Vec2>>, ParseError> {
+    let (x, y) = (length(tag, "x")?, length(tag, "y")?);
+    let (w, h) = (length(tag, "width")?, length(tag, "height")?);
+    if w <= 0.0 || h <= 0.0 { return Ok(vec![]) }
+    // a missing radius takes the value of the other one
+    let (rx, ry) = match (tag.attribute("rx").is_some(), tag.attribute("ry").is_some()) {
+        (true, false) => (length(tag, "rx")?, length(tag, "rx")?),
+        (false, true) => (length(tag, "ry")?, length(tag, "ry")?),
+        _ => (length(tag, "rx")?, length(tag, "ry")?)
+    };
+    let (rx, ry) = (rx.clamp(0.0, w / 2.0), ry.clamp(0.0, h / 2.0));
+    if rx == 0.0 || ry == 0.0 {
+        return Ok(vec![vec![Vec2::new(x, y), Vec2::new(x + w, y), Vec2::new(x + w, y + h), Vec2::new(x, y + h)]])
+    }
+    let d = format!(
+        "M {} {} H {} A {rx} {ry} 0 0 1 {} {} V {} A {rx} {ry} 0 0 1 {} {} H {} A {rx} {ry} 0 0 1 {} {} V {} A {rx} {ry} 0 0 1 {} {} Z",
+        x + rx, y, x + w - rx, x + w, y + ry, y + h - ry, x + w - rx, y + h, x + rx, x, y + h - ry, y + ry, x + rx, y
+    );
+    path_rings(&d, tag.offset, tolerance)
+}
+
+/// one ring per subpath of the path data. every subpath is closed, as it is when filled
+fn path_rings(d: &str, offset: usize, tolerance: f32) -> Result<Vec<Vec<Vec2>>, ParseError> {
+    let mut s = Numbers { text: d, pos: 0, offset };
+    let mut rings = vec![];
+    let mut ring: Vec<Vec2> = vec![];
+    let (mut cur, mut start) = (Vec2::ZERO, Vec2::ZERO);
+    let mut command: Option<char> = None;
+    // the last control point of a cubic or a quadratic curve, reflected by S and T
+    let mut cubic_control: Option<Vec2> = None;
+    let mut quad_control: Option<Vec2> = None;
+    loop {
+        s.skip_separators();
+        let Some(next) = s.rest().chars().next() else { break };
+        if next.is_ascii_alphabetic() {
+            s.pos += 1;
+            command = Some(next);
+        }
+        let Some(c) = command else {
+            return Err(s.error(if rings.is_empty() && ring.is_empty() { "path must start with a move to" } else { "expected a path command" }))
+        };
+        if rings.is_empty() && ring.is_empty() && !matches!(c, 'M' | 'm') {
+            return Err(ParseError::new("path must start with a move to", offset + s.pos - 1))
+        }
+        let relative = c.is_ascii_lowercase();
+        let base = if relative { cur } else { Vec2::ZERO };
+        let upper = c.to_ascii_uppercase();
+        if upper != 'M' && upper != 'Z' && ring.is_empty() {
+            // drawing on after a close path starts from the start of the last subpath
+            ring.push(cur);
+        }
+        let (mut next_cubic, mut next_quad) = (None, None);
+        match upper {
+            'M' => {
+                rings.push(std::mem::take(&mut ring));
+                cur = base + s.point()?;
+                start = cur;
+                ring.push(cur);
+                // further coordinate pairs are line tos
+                command = Some(if relative { 'l' } else { 'L' });
+            }
+            'L' => {
+                cur = base + s.point()?;
+                ring.push(cur);
+            }
+            'H' => {
+                cur = Vec2::new(base.x + s.number()?, cur.y);
+                ring.push(cur);
+            }
+            'V' => {
+                cur = Vec2::new(cur.x, base.y + s.number()?);
+                ring.push(cur);
+            }
+            'C' | 'S' => {
+                let c1 = if upper == 'C' { base + s.point()? } else { cubic_control.map_or(cur, |c| cur * 2.0 - c) };
+                let c2 = base + s.point()?;
+                let end = base + s.point()?;
+                flatten_cubic(&mut ring, [cur, c1, c2, end], tolerance, 0);
+                next_cubic = Some(c2);
+                cur = end;
+            }
+            'Q' | 'T' => {
+                let q = if upper == 'Q' { base + s.point()? } else { quad_control.map_or(cur, |c| cur * 2.0 - c) };
+                let end = base + s.point()?;
+                // a quadratic curve is a cubic one with its control points 2/3 of the way to q
+                flatten_cubic(&mut ring, [cur, cur + (q - cur) * (2.0 / 3.0), end + (q - end) * (2.0 / 3.0), end], tolerance, 0);
+                next_quad = Some(q);
+                cur = end;
+            }
+            'A' => {
+                let radii = s.point()?;
+                let angle = s.number()?;
+                let large = s.flag()?;
+                let sweep = s.flag()?;
+                let end = base + s.point()?;
+                flatten_arc(&mut ring, cur, radii, angle, large, sweep, end, tolerance);
+                cur = end;
+            }
+            'Z' => {
+                rings.push(std::mem::take(&mut ring));
+                cur = start;
+                command = None;
+            }
+            _ => return Err(ParseError::new(format!("unknown path command {}", c), offset + s.pos - 1))
+        }
+        cubic_control = next_cubic;
+        quad_control = next_quad;
+    }
+    rings.push(ring);
+    Ok(rings.into_iter().filter(|r| !r.is_empty()).collect())
+}
+
+fn dist_to_segment(p: Vec2, from: Vec2, to: Vec2) -> f32 {
+    let line = to - from;
+    let len = line.magnitude_squared();
+    let t = if len > 0.0 { (inner_product(&(p - from), &line) / len).clamp(0.0, 1.0) } else { 0.0 };
+    (p - (from + line * t)).magnitude()
+}
+
+/// the curve lies in the hull of its control points,
+/// so it is close enough to its chord once they are
+fn flatten_cubic(out: &mut Vec<Vec2>, [p0, p1, p2, p3]: [Vec2; 4], tolerance: f32, depth: u32) {
+    if depth >= MAX_DEPTH
+        || dist_to_segment(p1, p0, p3).max(dist_to_segment(p2, p0, p3)) <= tolerance {
+        out.push(p3);
+        return
+    }
+    // de casteljau at t = 0.5
+    let (p01, p12, p23) = ((p0 + p1) / 2.0, (p1 + p2) / 2.0, (p2 + p3) / 2.0);
+    let (p012, p123) = ((p01 + p12) / 2.0, (p12 + p23) / 2.0);
+    let mid = (p012 + p123) / 2.0;
+    flatten_cubic(out, [p0, p01, p012, mid], tolerance, depth + 1);
+    flatten_cubic(out, [mid, p123, p23, p3], tolerance, depth + 1);
+}
+
+/// an elliptical arc given by its end points, converted to its centre as in the SVG implementation notes
+#[allow(clippy::too_many_arguments)]
+fn flatten_arc(out: &mut Vec<Vec2>, from: Vec2, radii: Vec2, angle: f32, large: bool, sweep: bool, to: Vec2, tolerance: f32) {
+    let (mut rx, mut ry) = (radii.x.abs() as f64, radii.y.abs() as f64);
+    if from == to { return }
+    if rx == 0.0 || ry == 0.0 {
+        out.push(to);
+        return
+    }
+    let (sin, cos) = (angle as f64).to_radians().sin_cos();
+    let (dx, dy) = (((from.x - to.x) / 2.0) as f64, ((from.y - to.y) / 2.0) as f64);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+    // radii too small to reach the end point are scaled up
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let coef = (num / den).max(0.0).sqrt() * if large == sweep { -1.0 } else { 1.0 };
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let cx = cos * cx1 - sin * cy1 + (from.x + to.x) as f64 / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.y + to.y) as f64 / 2.0;
+    let angle_of = |x: f64, y: f64| y.atan2(x);
+    let theta = angle_of((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle_of((-x1 - cx1) / rx, (-y1 - cy1) / ry) - theta;
+    if sweep && delta < 0.0 { delta += 2.0 * PI }
+    if !sweep && delta > 0.0 { delta -= 2.0 * PI }
+    // the largest step whose chord stays within the tolerance
+    let r = rx.max(ry);
+    let step = if (tolerance as f64) < r { 2.0 * (1.0 - tolerance as f64 / r).acos() } else { PI / 2.0 };
+    let n = (delta.abs() / step.max(1e-3)).ceil().max(1.0) as usize;
+    for k in 1..n {
+        let t = theta + delta * k as f64 / n as f64;
+        let (x, y) = (rx * t.cos(), ry * t.sin());
+        out.push(Vec2::new((cx + x * cos - y * sin) as f32, (cy + x * sin + y * cos) as f32));
+    }
+    out.push(to);
+}
+
+/// a start or end tag. attribute values come with their byte offset in the document
+struct Tag<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str, usize)>,
+    offset: usize,
+    closing: bool,
+    self_closing: bool
+}
+
+impl<'a> Tag<'a> {
+    fn attribute(&self, name: &str) -> Option<(&'a str, usize)> {
+        self.attributes.iter().find(|(n, _, _)| *n == name).map(|(_, v, o)| (*v, *o))
+    }
+
+    /// a presentation attribute, which a declaration in `style` overrides
+    fn property(&self, name: &str) -> Option<(&'a str, usize)> {
+        let from_style = self.attribute("style").and_then(|(style, offset)| {
+            let mut start = 0;
+            style.split(';').find_map(|declaration| {
+                let declaration_start = start;
+                start += declaration.len() + 1;
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == name).then(|| (value.trim(), offset + declaration_start + key.len() + 1))
+            })
+        });
+        from_style.or_else(|| self.attribute(name).map(|(v, o)| (v.trim(), o)))
+    }
+}
+
+/// just enough of XML to find the tags and their attributes
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl<'a> Scanner<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), ParseError> {
+        match self.rest().find(end) {
+            Some(i) => { self.pos += i + end.len(); Ok(()) }
+            None => Err(ParseError::new(format!("expected '{}'", end), self.text.len()))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start().len();
+    }
+
+    fn name(&mut self) -> &'a str {
+        let len = self.rest().find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=')).unwrap_or(self.rest().len());
+        let name = &self.rest()[..len];
+        self.pos += len;
+        // namespace prefixes such as svg:path are dropped
+        name.rsplit(':').next().unwrap()
+    }
+
+    fn next_tag(&mut self) -> Result<Option<Tag<'a>>, ParseError> {
+        loop {
+            let Some(i) = self.rest().find('<') else { return Ok(None) };
+            self.pos += i;
+            let start = self.pos;
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                let closing = self.rest().starts_with("</");
+                self.pos += if closing { 2 } else { 1 };
+                let name = self.name();
+                if name.is_empty() {
+                    return Err(ParseError::new("expected a tag name", self.pos))
+                }
+                let mut attributes = vec![];
+                loop {
+                    self.skip_whitespace();
+                    if self.rest().starts_with("/>") {
+                        self.pos += 2;
+                        return Ok(Some(Tag { name, attributes, offset: start, closing, self_closing: true }))
+                    }
+                    if self.rest().starts_with('>') {
+                        self.pos += 1;
+                        return Ok(Some(Tag { name, attributes, offset: start, closing, self_closing: false }))
+                    }
+                    let attribute_start = self.pos;
+                    let attribute = self.name();
+                    if attribute.is_empty() {
+                        return Err(ParseError::new("expected an attribute or '>'", attribute_start))
+                    }
+                    self.skip_whitespace();
+                    if !self.rest().starts_with('=') {
+                        return Err(ParseError::new("expected '='", self.pos))
+                    }
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    let Some(quote) = self.rest().chars().next().filter(|c| matches!(c, '"' | '\'')) else {
+                        return Err(ParseError::new("expected a quoted value", self.pos))
+                    };
+                    let value_start = self.pos + 1;
+                    let Some(len) = self.text[value_start..].find(quote) else {
+                        return Err(ParseError::new("unterminated attribute value", self.pos))
+                    };
+                    attributes.push((attribute, &self.text[value_start..(value_start + len)], value_start));
+                    self.pos = value_start + len + 1;
+                }
+            }
+        }
+    }
+}
+
+/// numbers in attribute values such as `points`, `d` and `transform`,
+/// where separators are optional: `M1.5.5-2` is `M 1.5 0.5 -2`
+struct Numbers<'a> {
+    text: &'a str,
+    pos: usize,
+    /// where the value starts in the document
+    offset: usize
+}
+
+impl<'a> Numbers<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(message, self.offset + self.pos)
+    }
+
+    fn skip_separators(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start_matches(|c: char| c.is_whitespace() || c == ',').len();
+    }
+
+    fn number(&mut self) -> Result<f32, ParseError> {
+        self.skip_separators();
+        let bytes = self.rest().as_bytes();
+        let mut len = 0;
+        let digits = |len: &mut usize| {
+            let start = *len;
+            while *len < bytes.len() && bytes[*len].is_ascii_digit() { *len += 1 }
+            *len > start
+        };
+        if len < bytes.len() && matches!(bytes[len], b'+' | b'-') { len += 1 }
+        let mut any = digits(&mut len);
+        if len < bytes.len() && bytes[len] == b'.' {
+            len += 1;
+            any |= digits(&mut len);
+        }
+        if any && len < bytes.len() && matches!(bytes[len], b'e' | b'E') {
+            let mut exponent = len + 1;
+            if exponent < bytes.len() && matches!(bytes[exponent], b'+' | b'-') { exponent += 1 }
+            if digits(&mut exponent) { len = exponent }
+        }
+        if !any { return Err(self.error("expected a number")) }
+        let value = self.rest()[..len].parse::<f32>().map_err(|_| self.error("expected a number"))?;
+        self.pos += len;
+        Ok(value)
+    }
+
+    fn point(&mut self) -> Result<Vec2, ParseError> {
+        Ok(Vec2::new(self.number()?, self.number()?))
+    }
+
+    /// arc flags are a single digit, which may run into the next number
+    fn flag(&mut self) -> Result<bool, ParseError> {
+        self.skip_separators();
+        let flag = match self.rest().chars().next() {
+            Some('0') => false,
+            Some('1') => true,
+            _ => return Err(self.error("expected a flag"))
+        };
+        self.pos += 1;
+        Ok(flag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::area;
+    use crate::io::svg::{parse_svg, to_svg};
+    use crate::io::ParseError;
+    use crate::polygon::{is_polygon_clockwise, split_rings};
+
+    #[test]
+    fn test_shapes() {
+        let svg = r#"<?xml version="1.0"?>
+            <!-- shapes from a designer -->
+            <svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+                <rect x="0" y="0" width="4px" height="3"/>
+                <polygon points="0,0 4,0 0,4"/>
+                <g transform="translate(10 0) scale(2)">
+                    <polyline points="0 0 1 0 1 1 0 1" />
+                </g>
+                <defs><rect width="100" height="100"/></defs>
+            </svg>"#;
+        let polygons = parse_svg(svg, 0.1).unwrap();
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(polygons.iter().map(|p| area(p)).collect::<Vec<_>>(), vec![12.0, 8.0, 4.0]);
+        assert!(polygons.iter().all(|p| !is_polygon_clockwise(p)), "outer rings should be counter-clockwise");
+        assert_eq!(polygons[2][0].from.x.min(polygons[2][2].from.x), 10.0);
+    }
+
+    #[test]
+    fn test_fill_rule() {
+        // both squares are drawn clockwise
+        let d = "M 0 0 H 4 V 4 H 0 Z m 1 1 h 2 v 2 h -2 z";
+        let polygons = |attributes: &str| parse_svg(&format!("<svg><path {} d=\"{}\"/></svg>", attributes, d), 0.1).unwrap();
+        assert_eq!(area(&polygons("")[0]), 16.0);
+        assert_eq!(area(&polygons("fill-rule=\"evenodd\"")[0]), 12.0);
+        assert_eq!(area(&polygons("fill-rule=\"evenodd\" style=\"fill:red; fill-rule: nonzero\"")[0]), 16.0);
+        let inherited = format!("<svg><g fill-rule=\"evenodd\"><path d=\"{}\"/></g></svg>", d);
+        assert_eq!(split_rings(&parse_svg(&inherited, 0.1).unwrap()[0]).len(), 2);
+    }
+
+    #[test]
+    fn test_curves() {
+        let circle = "<svg><path d='M 10 0 A 10 10 0 0 1 -10 0 A 10 10 0 0 1 10 0 Z'/></svg>";
+        let polygon = &parse_svg(circle, 0.01).unwrap()[0];
+        // the chords cut off at most the tolerance times the perimeter
+        let expected = std::f32::consts::PI * 100.0;
+        assert!(area(polygon) < expected && area(polygon) > expected - 0.01 * 20.0 * std::f32::consts::PI, "circle area {}", area(polygon));
+        let coarse = &parse_svg(circle, 1.0).unwrap()[0];
+        assert!(coarse.len() < polygon.len());
+        // a cubic and a quadratic curve, with the smooth forms and relative coordinates
+        let curves = "<svg><path d='M0 0C0-5 10-5 10 0s-10 5-10 0zM20 0q5-5 10 0t-10 0z'/></svg>";
+        let polygons = parse_svg(curves, 0.001).unwrap();
+        assert_eq!(split_rings(&polygons[0]).len(), 2);
+        // each cubic bump encloses 6 times the height of its control points,
+        // each quadratic one 2/3 of the triangle its control point makes with the chord
+        assert!((area(&polygons[0]) - (2.0 * 6.0 * 5.0 + 2.0 * 2.0 / 3.0 * 25.0)).abs() < 0.05);
+        let rounded = parse_svg("<svg><rect width='10' height='10' rx='5'/></svg>", 0.01).unwrap();
+        assert!((area(&rounded[0]) - 25.0 * std::f32::consts::PI).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let svg = "<svg><path fill-rule='evenodd' d='M 0 0 H 4 V 4 H 0 Z M 1 1 H 3 V 3 H 1 Z'/><rect x='10' width='1' height='1'/></svg>";
+        let polygons = parse_svg(svg, 0.1).unwrap();
+        let written = to_svg(&polygons);
+        assert!(written.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 11 4\""));
+        let read_back = parse_svg(&written, 0.1).unwrap();
+        assert_eq!(read_back.iter().map(|p| area(p)).collect::<Vec<_>>(), vec![12.0, 1.0]);
+        assert_eq!(read_back.iter().map(|p| split_rings(p).len()).collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_svg("<svg><path d='M 0 0 L 1 x'/></svg>", 0.1).unwrap_err(), ParseError::new("expected a number", 24));
+        assert_eq!(parse_svg("<svg><path d='L 0 0'/></svg>", 0.1).unwrap_err(), ParseError::new("path must start with a move to", 14));
+        assert_eq!(parse_svg("<svg><rect width='10%' height='1'/></svg>", 0.1).unwrap_err(), ParseError::new("unsupported unit", 20));
+        assert_eq!(parse_svg("<svg><rect width=10/></svg>", 0.1).unwrap_err(), ParseError::new("expected a quoted value", 17));
+        assert_eq!(parse_svg("<svg><g transform='spin(1)'/></svg>", 0.1).unwrap_err(), ParseError::new("invalid transform spin", 19));
+    }
+}
@@ -0,0 +1,220 @@
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::io::{polygon_from_rings, Geometry, ParseError};
+use crate::polygon::split_rings;
+
+const LINE_STRING: u32 = 2;
+const POLYGON: u32 = 3;
+const MULTI_POLYGON: u32 = 6;
+
+/// reads a POLYGON, MULTIPOLYGON or LINESTRING in either byte order.
+/// ISO and PostGIS (EWKB) Z and M values are dropped, and so is an EWKB SRID.
+/// rings are reoriented like in `parse_wkt`
+pub fn parse_wkb (bytes: &[u8]) -> Result<Geometry, ParseError> {
+    let mut reader = Reader { bytes, pos: 0, little_endian: true };
+    let geometry = reader.geometry()?;
+    if reader.pos != bytes.len() {
+        return Err(ParseError::new("unexpected data after the geometry", reader.pos))
+    }
+    Ok(geometry)
+}
+
+/// WKB in hexadecimal, as PostGIS prints it
+pub fn parse_wkb_hex (text: &str) -> Result<Geometry, ParseError> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return Err(ParseError::new("odd number of hex digits", text.len()))
+    }
+    let bytes = (0..text.len()).step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..(i + 2)).unwrap_or(""), 16)
+            .map_err(|_| ParseError::new("invalid hex digit", i)))
+        .collect::<Result<Vec<u8>, ParseError>>()?;
+    // offsets in the binary data are half the offsets in the text
+    parse_wkb(&bytes).map_err(|e| ParseError { offset: e.offset.map(|o| o * 2), ..e })
+}
+
+/// little endian, 2D, closing every ring by repeating its first vertex
+pub fn to_wkb (geometry: &Geometry) -> Vec<u8> {
+    let mut out = vec![];
+    match geometry {
+        Geometry::Polygon(polygon) => write_polygon(&mut out, polygon),
+        Geometry::MultiPolygon(polygons) => {
+            write_header(&mut out, MULTI_POLYGON, polygons.len());
+            polygons.iter().for_each(|p| write_polygon(&mut out, p));
+        }
+        Geometry::LineString(points) => {
+            write_header(&mut out, LINE_STRING, points.len());
+            write_points(&mut out, points);
+        }
+    }
+    out
+}
+
+pub fn to_wkb_hex (geometry: &Geometry) -> String {
+    to_wkb(geometry).iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn write_header(out: &mut Vec<u8>, kind: u32, count: usize) {
+    out.push(1);
+    out.extend(kind.to_le_bytes());
+    out.extend((count as u32).to_le_bytes());
+}
+
+fn write_points(out: &mut Vec<u8>, points: &[Vec2]) {
+    for p in points {
+        out.extend((p.x as f64).to_le_bytes());
+        out.extend((p.y as f64).to_le_bytes());
+    }
+}
+
+fn write_polygon(out: &mut Vec<u8>, polygon: &[Edge]) {
+    let rings = split_rings(polygon);
+    write_header(out, POLYGON, rings.len());
+    for ring in rings {
+        let closed: Vec<Vec2> = ring.iter().map(|e| e.from).chain([ring[0].from]).collect();
+        out.extend((closed.len() as u32).to_le_bytes());
+        write_points(out, &closed);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    little_endian: bool
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ParseError> {
+        let bytes = self.bytes.get(self.pos..(self.pos + N))
+            .ok_or_else(|| ParseError::new("unexpected end of data", self.bytes.len()))?;
+        self.pos += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> Result<u32, ParseError> {
+        let bytes = self.take::<4>()?;
+        Ok(if self.little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) })
+    }
+
+    fn f64(&mut self) -> Result<f64, ParseError> {
+        let bytes = self.take::<8>()?;
+        Ok(if self.little_endian { f64::from_le_bytes(bytes) } else { f64::from_be_bytes(bytes) })
+    }
+
+    /// a count, checked against the remaining data so that a corrupt count fails early
+    fn count(&mut self, item_size: usize) -> Result<usize, ParseError> {
+        let start = self.pos;
+        let count = self.u32()? as usize;
+        if count.saturating_mul(item_size) > self.bytes.len() - self.pos {
+            return Err(ParseError::new(format!("count {} is larger than the data", count), start))
+        }
+        Ok(count)
+    }
+
+    fn points(&mut self, dimensions: usize) -> Result<Vec<Vec2>, ParseError> {
+        let count = self.count(8 * dimensions)?;
+        (0..count).map(|_| {
+            let x = self.f64()?;
+            let y = self.f64()?;
+            for _ in 2..dimensions { self.f64()?; }
+            Ok(Vec2::new(x as f32, y as f32))
+        }).collect()
+    }
+
+    /// byte order and type, returning the base type and the number of ordinates per point
+    fn header(&mut self) -> Result<(u32, usize), ParseError> {
+        let start = self.pos;
+        self.little_endian = match self.take::<1>()?[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(ParseError::new("invalid byte order", start))
+        };
+        let kind = self.u32()?;
+        // EWKB keeps flags in the high bits, ISO adds 1000 for Z, 2000 for M and 3000 for ZM
+        let iso = (kind & 0x0fff_ffff) / 1000;
+        let has_z = kind & 0x8000_0000 != 0 || iso == 1 || iso == 3;
+        let has_m = kind & 0x4000_0000 != 0 || iso == 2 || iso == 3;
+        if kind & 0x2000_0000 != 0 {
+            self.u32()?; // srid
+        }
+        Ok(((kind & 0x0fff_ffff) % 1000, 2 + has_z as usize + has_m as usize))
+    }
+
+    fn polygon(&mut self, dimensions: usize) -> Result<Vec<Edge>, ParseError> {
+        let ring_count = self.count(4)?;
+        let mut rings = vec![];
+        for _ in 0..ring_count {
+            let start = self.pos;
+            let mut ring = self.points(dimensions)?;
+            if ring.len() < 4 {
+                return Err(ParseError::new("a ring needs at least 4 points", start))
+            }
+            if ring.first() != ring.last() {
+                return Err(ParseError::new("ring is not closed", start))
+            }
+            ring.pop();
+            rings.push(ring);
+        }
+        Ok(polygon_from_rings(&rings))
+    }
+
+    fn geometry(&mut self) -> Result<Geometry, ParseError> {
+        let start = self.pos;
+        let (kind, dimensions) = self.header()?;
+        match kind {
+            LINE_STRING => Ok(Geometry::LineString(self.points(dimensions)?)),
+            POLYGON => Ok(Geometry::Polygon(self.polygon(dimensions)?)),
+            MULTI_POLYGON => {
+                let count = self.count(9)?;
+                let mut polygons = vec![];
+                for _ in 0..count {
+                    let part_start = self.pos;
+                    match self.header()? {
+                        (POLYGON, dimensions) => polygons.push(self.polygon(dimensions)?),
+                        _ => return Err(ParseError::new("a multi polygon may only contain polygons", part_start))
+                    }
+                }
+                Ok(Geometry::MultiPolygon(polygons))
+            }
+            _ => Err(ParseError::new(format!("unsupported geometry type {}", kind), start))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::io::wkb::{parse_wkb, parse_wkb_hex, to_wkb, to_wkb_hex};
+    use crate::io::wkt::parse_wkt;
+    use crate::io::{Geometry, ParseError};
+
+    #[test]
+    fn test_round_trip() {
+        let geometry = parse_wkt("MULTIPOLYGON (((0 0, 0 4, 4 4, 4 0, 0 0), (1 1, 2 1, 2 2, 1 2, 1 1)), ((10 0, 10 1, 11 1, 11 0, 10 0)))").unwrap();
+        assert_eq!(parse_wkb(&to_wkb(&geometry)).unwrap(), geometry);
+        assert_eq!(parse_wkb_hex(&to_wkb_hex(&geometry)).unwrap(), geometry);
+        let line = Geometry::LineString(vec![Vec2::new(1.0, 2.0), Vec2::new(3.0, 4.0)]);
+        assert_eq!(parse_wkb(&to_wkb(&line)).unwrap(), line);
+    }
+
+    #[test]
+    fn test_postgis() {
+        // big endian EWKB with SRID 4326 and Z values: the triangle (0 0 5, 1 0 5, 1 1 5)
+        let mut bytes = vec![0];
+        bytes.extend((3u32 | 0x8000_0000 | 0x2000_0000).to_be_bytes());
+        bytes.extend(4326u32.to_be_bytes());
+        bytes.extend(1u32.to_be_bytes());
+        bytes.extend(4u32.to_be_bytes());
+        for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0)] {
+            for v in [x, y, 5.0f64] {
+                bytes.extend(v.to_be_bytes());
+            }
+        }
+        assert_eq!(parse_wkb(&bytes).unwrap(), parse_wkt("POLYGON ((0 0, 1 0, 1 1, 0 0))").unwrap());
+        assert_eq!(parse_wkb(&bytes[..40]).unwrap_err(), ParseError::new("count 4 is larger than the data", 13));
+        assert_eq!(parse_wkb_hex("0101000000").unwrap_err(), ParseError::new("unsupported geometry type 1", 0));
+        assert_eq!(parse_wkb_hex("01x2").unwrap_err(), ParseError::new("invalid hex digit", 2));
+    }
+}
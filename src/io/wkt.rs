@@ -0,0 +1,230 @@
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::io::{polygon_from_rings, Geometry, ParseError};
+use crate::polygon::split_rings;
+
+/// reads a POLYGON, MULTIPOLYGON or LINESTRING.
+/// a PostGIS style `SRID=...;` prefix is skipped, and so are Z and M values.
+/// rings are reoriented: the first ring of a polygon is its outer ring, the others are holes
+pub fn parse_wkt (text: &str) -> Result<Geometry, ParseError> {
+    let mut p = Parser { text, pos: 0 };
+    p.skip_whitespace();
+    if p.rest().get(..5).is_some_and(|s| s.eq_ignore_ascii_case("SRID=")) {
+        match p.rest().find(';') {
+            Some(end) => p.pos += end + 1,
+            None => return Err(p.error("expected ';' after SRID"))
+        }
+    }
+    let start = p.pos;
+    let geometry = match p.word().as_str() {
+        "POLYGON" => {
+            p.dimensions();
+            Geometry::Polygon(if p.empty() { vec![] } else { p.polygon()? })
+        }
+        "MULTIPOLYGON" => {
+            p.dimensions();
+            let mut polygons = vec![];
+            if !p.empty() {
+                p.expect('(')?;
+                loop {
+                    polygons.push(p.polygon()?);
+                    if !p.next_in_list()? { break }
+                }
+            }
+            Geometry::MultiPolygon(polygons)
+        }
+        "LINESTRING" => {
+            p.dimensions();
+            Geometry::LineString(if p.empty() { vec![] } else { p.points()? })
+        }
+        "" => return Err(ParseError::new("expected a geometry type", start)),
+        other => return Err(ParseError::new(format!("unsupported geometry type {}", other), start))
+    };
+    p.skip_whitespace();
+    if !p.rest().is_empty() {
+        return Err(p.error("unexpected text after the geometry"))
+    }
+    Ok(geometry)
+}
+
+/// rings are written in the crate's orientation and closed by repeating the first vertex
+pub fn to_wkt (geometry: &Geometry) -> String {
+    match geometry {
+        Geometry::Polygon(polygon) if polygon.is_empty() => "POLYGON EMPTY".to_string(),
+        Geometry::Polygon(polygon) => format!("POLYGON {}", polygon_text(polygon)),
+        Geometry::MultiPolygon(polygons) if polygons.is_empty() => "MULTIPOLYGON EMPTY".to_string(),
+        Geometry::MultiPolygon(polygons) => format!("MULTIPOLYGON ({})",
+            polygons.iter().map(|p| polygon_text(p)).collect::<Vec<_>>().join(", ")),
+        Geometry::LineString(points) if points.is_empty() => "LINESTRING EMPTY".to_string(),
+        Geometry::LineString(points) => format!("LINESTRING {}", points_text(points))
+    }
+}
+
+fn points_text(points: &[Vec2]) -> String {
+    format!("({})", points.iter().map(|p| format!("{} {}", p.x, p.y)).collect::<Vec<_>>().join(", "))
+}
+
+fn polygon_text(polygon: &[Edge]) -> String {
+    format!("({})", split_rings(polygon).iter().map(|ring| {
+        let closed: Vec<Vec2> = ring.iter().map(|e| e.from).chain([ring[0].from]).collect();
+        points_text(&closed)
+    }).collect::<Vec<_>>().join(", "))
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        ParseError::new(message, self.pos)
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start().len();
+    }
+
+    /// a keyword, in upper case. empty if there is none
+    fn word(&mut self) -> String {
+        self.skip_whitespace();
+        let len = self.rest().find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(self.rest().len());
+        let word = self.rest()[..len].to_ascii_uppercase();
+        self.pos += len;
+        word
+    }
+
+    /// skips Z, M or ZM after the geometry type
+    fn dimensions(&mut self) {
+        let before = self.pos;
+        if !matches!(self.word().as_str(), "Z" | "M" | "ZM") {
+            self.pos = before;
+        }
+    }
+
+    fn empty(&mut self) -> bool {
+        let before = self.pos;
+        if self.word() == "EMPTY" { return true }
+        self.pos = before;
+        false
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.rest().starts_with(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    /// after an item of a list: true if another item follows, false if the list is closed
+    fn next_in_list(&mut self) -> Result<bool, ParseError> {
+        self.skip_whitespace();
+        match self.rest().chars().next() {
+            Some(',') => { self.pos += 1; Ok(true) }
+            Some(')') => { self.pos += 1; Ok(false) }
+            _ => Err(self.error("expected ',' or ')'"))
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, ParseError> {
+        self.skip_whitespace();
+        let len = self.rest()
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')))
+            .unwrap_or(self.rest().len());
+        let value = self.rest()[..len].parse::<f64>().map_err(|_| self.error("expected a number"))?;
+        self.pos += len;
+        Ok(value as f32)
+    }
+
+    /// x and y, any further ordinates are read and dropped
+    fn coordinate(&mut self) -> Result<Vec2, ParseError> {
+        let x = self.number()?;
+        let y = self.number()?;
+        loop {
+            self.skip_whitespace();
+            if self.rest().is_empty() || self.rest().starts_with([',', ')']) { break }
+            self.number()?;
+        }
+        Ok(Vec2::new(x, y))
+    }
+
+    fn points(&mut self) -> Result<Vec<Vec2>, ParseError> {
+        self.expect('(')?;
+        let mut points = vec![];
+        loop {
+            points.push(self.coordinate()?);
+            if !self.next_in_list()? { break }
+        }
+        Ok(points)
+    }
+
+    fn polygon(&mut self) -> Result<Vec<Edge>, ParseError> {
+        self.expect('(')?;
+        let mut rings = vec![];
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let mut ring = self.points()?;
+            if ring.len() < 4 {
+                return Err(ParseError::new("a ring needs at least 4 points", start))
+            }
+            if ring.first() != ring.last() {
+                return Err(ParseError::new("ring is not closed", start))
+            }
+            ring.pop();
+            rings.push(ring);
+            if !self.next_in_list()? { break }
+        }
+        Ok(polygon_from_rings(&rings))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::io::wkt::{parse_wkt, to_wkt};
+    use crate::io::{Geometry, ParseError};
+    use crate::polygon::{is_polygon_clockwise, split_rings};
+
+    #[test]
+    fn test_parse() {
+        let geometry = parse_wkt("POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0), (1 1, 1 2, 2 2, 2 1, 1 1))").unwrap();
+        let rings = split_rings(&geometry.to_polygon());
+        assert_eq!(rings.len(), 2);
+        assert!(!is_polygon_clockwise(&rings[0]), "outer ring should be counter-clockwise");
+        assert!(is_polygon_clockwise(&rings[1]), "hole should be clockwise");
+        let multi = parse_wkt("SRID=4326;multipolygon Z (((0 0 1, 1 0 1, 1 1 1, 0 0 1)), ((5 5 0, 6 5 0, 6 6 0, 5 5 0)))").unwrap();
+        assert!(matches!(&multi, Geometry::MultiPolygon(p) if p.len() == 2));
+        assert_eq!(parse_wkt("LINESTRING (1 2, 3.5 -4e1)").unwrap(),
+                   Geometry::LineString(vec![Vec2::new(1.0, 2.0), Vec2::new(3.5, -40.0)]));
+        assert_eq!(parse_wkt(" POLYGON EMPTY ").unwrap(), Geometry::Polygon(vec![]));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse_wkt("POLYGON ((0 0, 1 0, 1 1, 0 0)").unwrap_err(), ParseError::new("expected ',' or ')'", 29));
+        assert_eq!(parse_wkt("POLYGON ((0 0, 1 0, 1 1, 0 1))").unwrap_err(), ParseError::new("ring is not closed", 9));
+        assert_eq!(parse_wkt("POLYGON ((0 0, 1 x, 1 1, 0 0))").unwrap_err(), ParseError::new("expected a number", 17));
+        assert_eq!(parse_wkt("POINT (1 2)").unwrap_err(), ParseError::new("unsupported geometry type POINT", 0));
+        assert_eq!(parse_wkt("LINESTRING (1 2) x").unwrap_err().offset, Some(17));
+        assert_eq!(parse_wkt("abcd€").unwrap_err().offset, Some(0));
+        assert!(parse_wkt("POLYGON ((0 0, 1 €, 1 1, 0 0))").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = "MULTIPOLYGON (((0 0, 0 4, 4 4, 4 0, 0 0), (1 1, 2 1, 2 2, 1 2, 1 1)), ((10 0, 10 1, 11 1, 11 0, 10 0)))";
+        let geometry = parse_wkt(text).unwrap();
+        assert_eq!(to_wkt(&geometry), text);
+        assert_eq!(to_wkt(&Geometry::from_polygon(&geometry.to_polygon())), text);
+    }
+}
@@ -0,0 +1,18 @@
+pub mod ui;
+pub mod clipping;
+pub mod boolean;
+pub mod minkowski;
+pub mod triangulation;
+pub mod decomposition;
+pub mod simplify;
+pub mod validation;
+pub mod generator;
+pub mod io;
+pub mod render;
+pub mod raster;
+pub mod edge;
+pub mod vec;
+pub mod polygon;
+/// polygons shared by the tests
+#[cfg(test)]
+mod fixtures;
@@ -1,13 +1,7 @@
-extern crate core;
+use std::path::PathBuf;
 
-use crate::ui::run_loop;
-
-mod ui;
-mod clipping;
-mod edge;
-mod vec;
-mod polygon;
+use polygon_clip::ui::run_loop;
 
 fn main() {
-    run_loop()
+    run_loop(std::env::args_os().nth(1).map(PathBuf::from))
 }
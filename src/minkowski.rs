@@ -0,0 +1,149 @@
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::union_all;
+use crate::edge::Edge;
+use crate::polygon::{point_vec_to_edges, signed_area};
+use crate::vec::cross_product;
+
+/// minkowski sum of two convex rings, merging their edges by angle.
+/// the result is a counter-clockwise ring
+pub fn minkowski_sum_convex (a: &[Edge], b: &[Edge]) -> Vec<Edge> {
+    let p = convex_points(a);
+    let q = convex_points(b);
+    let (mut i, mut j) = (0, 0);
+    let mut res = vec![];
+    while i < p.len() || j < q.len() {
+        res.push(p[i % p.len()] + q[j % q.len()]);
+        let edge_p = p[(i + 1) % p.len()] - p[i % p.len()];
+        let edge_q = q[(j + 1) % q.len()] - q[j % q.len()];
+        let c = cross_product(&edge_p, &edge_q);
+        if c >= 0.0 && i < p.len() { i += 1 }
+        if c <= 0.0 && j < q.len() { j += 1 }
+    }
+    res.reverse();
+    point_vec_to_edges(&res)
+}
+
+/// minkowski sum of two polygons, which may have holes.
+/// both are split into convex pieces, the pieces are summed pairwise and the sums merged by a union
+pub fn minkowski_sum (a: &[Edge], b: &[Edge]) -> Vec<Edge> {
+    let pieces_a = convex_pieces(a);
+    let pieces_b = convex_pieces(b);
+    if pieces_a.len() == 1 && pieces_b.len() == 1 {
+        return minkowski_sum_convex(&pieces_a[0], &pieces_b[0])
+    }
+    let sums: Vec<Vec<Edge>> = pieces_a.iter()
+        .flat_map(|pa| pieces_b.iter().map(|pb| minkowski_sum_convex(pa, pb)))
+        .collect();
+    union_all(&sums)
+}
+
+/// the difference a ⊕ (-b) used for collision detection:
+/// the polygons overlap iff the result contains the origin
+pub fn minkowski_difference (a: &[Edge], b: &[Edge]) -> Vec<Edge> {
+    // point reflection keeps the orientation of the rings
+    let neg_b: Vec<Edge> = b.iter().map(|e| Edge { from: e.from * -1.0, to: e.to * -1.0 }).collect();
+    minkowski_sum(a, &neg_b)
+}
+
+/// vertices in clockwise order (positive area with y downwards), starting from the lowest y, then lowest x
+fn convex_points(ring: &[Edge]) -> Vec<Vec2> {
+    let mut points: Vec<Vec2> = ring.iter().map(|e| e.from).collect();
+    if signed_area(ring) < 0.0 {
+        points.reverse();
+    }
+    let start = points.iter().enumerate()
+        .min_by(|(_, p1), (_, p2)| p1.y.total_cmp(&p2.y).then(p1.x.total_cmp(&p2.x)))
+        .map(|(i, _)| i)
+        .unwrap();
+    points.rotate_left(start);
+    points
+}
+
+/// convex pieces covering the polygon, which may have holes: the polygon is cut into vertical slabs
+/// at the x of every vertex, and within a slab the parts between the edges crossing it are trapezoids.
+/// edges pair up from the top, since rings do not cross
+fn convex_pieces(polygon: &[Edge]) -> Vec<Vec<Edge>> {
+    let mut xs: Vec<f32> = polygon.iter().map(|e| e.from.x).collect();
+    xs.sort_by(f32::total_cmp);
+    xs.dedup();
+    let y_at = |e: &Edge, x: f32| {
+        if x == e.from.x { return e.from.y }
+        if x == e.to.x { return e.to.y }
+        let t = (x as f64 - e.from.x as f64) / (e.to.x as f64 - e.from.x as f64);
+        (e.from.y as f64 + (e.to.y as f64 - e.from.y as f64) * t) as f32
+    };
+    let mut pieces = vec![];
+    for (x0, x1) in xs.iter().zip(xs.iter().skip(1)) {
+        let mid = (x0 + x1) / 2.0;
+        let mut crossing: Vec<&Edge> = polygon.iter()
+            .filter(|e| e.from.x.min(e.to.x) <= *x0 && e.from.x.max(e.to.x) >= *x1)
+            .collect();
+        crossing.sort_by(|a, b| y_at(a, mid).total_cmp(&y_at(b, mid)));
+        for pair in crossing.chunks_exact(2) {
+            let (top, bottom) = (pair[0], pair[1]);
+            let mut points = vec![
+                Vec2::new(*x0, y_at(top, *x0)), Vec2::new(*x0, y_at(bottom, *x0)),
+                Vec2::new(*x1, y_at(bottom, *x1)), Vec2::new(*x1, y_at(top, *x1))
+            ];
+            // where the edges meet, the trapezoid is a triangle
+            points.dedup();
+            if points.len() >= 3 {
+                pieces.push(point_vec_to_edges(&points));
+            }
+        }
+    }
+    pieces
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::fixtures::{area, ring};
+    use crate::minkowski::{minkowski_difference, minkowski_sum, minkowski_sum_convex};
+    use crate::polygon::{is_point_in_polygon, is_polygon_clockwise, split_rings};
+
+    #[test]
+    fn test_convex() {
+        let square = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        let triangle = ring(&[(0.0, 0.0), (0.0, 2.0), (2.0, 0.0)]);
+        let sum = minkowski_sum_convex(&square, &triangle);
+        assert!(!is_polygon_clockwise(&sum), "result should be counter-clockwise");
+        assert_eq!(sum.len(), 5);
+        assert_eq!(area(&sum), 1.0 + 2.0 + 2.0 + 2.0);
+        assert_eq!(area(&minkowski_sum(&square, &square)), 4.0);
+    }
+
+    #[test]
+    fn test_concave() {
+        // L shape: 2x2 square without the lower right 1x1 quarter
+        let l_shape = ring(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]);
+        let square = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        let sum = minkowski_sum(&l_shape, &square);
+        assert_eq!(split_rings(&sum).len(), 1);
+        assert_eq!(sum.len(), 6);
+        assert_eq!(area(&sum), 8.0);
+    }
+
+    #[test]
+    fn test_hole() {
+        let mut frame = ring(&[(0.0, 0.0), (0.0, 6.0), (6.0, 6.0), (6.0, 0.0)]);
+        frame.extend(ring(&[(2.0, 2.0), (4.0, 2.0), (4.0, 4.0), (2.0, 4.0)]));
+        let small = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        let sum = minkowski_sum(&frame, &small);
+        assert_eq!(area(&sum), 49.0 - 1.0);
+        assert!(!is_point_in_polygon(Vec2 { x: 3.5, y: 3.5 }, &sum));
+        assert!(is_point_in_polygon(Vec2 { x: 2.5, y: 2.5 }, &sum));
+    }
+
+    #[test]
+    fn test_difference() {
+        let a = ring(&[(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)]);
+        let near = ring(&[(1.0, 1.0), (1.0, 3.0), (3.0, 3.0), (3.0, 1.0)]);
+        let far = ring(&[(5.0, 5.0), (5.0, 6.0), (6.0, 6.0), (6.0, 5.0)]);
+        let origin = Vec2 { x: 0.0, y: 0.0 };
+        assert!(is_point_in_polygon(origin, &minkowski_difference(&a, &near)));
+        assert!(!is_point_in_polygon(origin, &minkowski_difference(&a, &far)));
+    }
+}
@@ -1,6 +1,10 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
 use speedy2d::dimen::{Vec2, Vector2};
 
 use crate::edge::Edge;
+use crate::validation::is_ring_simple;
 use crate::vec::{cross_product, inner_product};
 
 /// edges are in counter-clockwise order
@@ -13,6 +17,14 @@ pub fn is_point_in_polygon (point: Vec2, polygon: &[Edge]) -> bool {
     winding.abs() > 5e-4
 }
 
+/// how many times the ring winds around the point, positive for clockwise rings
+pub fn winding_number (point: Vec2, ring: &[Edge]) -> i32 {
+    let winding = ring.iter()
+        .map(|edge| get_directed_angle(edge.from - point, edge.to - point))
+        .sum::<f64>();
+    (winding / (2.0 * std::f64::consts::PI)).round() as i32
+}
+
 /// calculate the directed angle between 2 vectors
 /// clockwise = + angle
 /// we have to use f64 in this function internally to avoid loss of precision
@@ -34,4 +46,396 @@ pub fn is_polygon_clockwise (polygon: &[Edge]) -> bool {
         .sum::<f64>();
 
     total_angle > 0.0
-}
\ No newline at end of file
+}
+/// connect the points into a closed ring of edges
+pub fn point_vec_to_edges(points: &[Vec2]) -> Vec<Edge> {
+    if points.len() < 3 {
+        panic!("Malformed polygon!")
+    }
+    let mut ret: Vec<Edge> = vec![];
+    for (i, point) in points.iter().enumerate() {
+        if i == points.len() - 1 { break; }
+        ret.push(Edge { from: *point, to: points[i + 1] })
+    }
+    ret.push(Edge { from: *points.last().unwrap(), to: points[0] });
+    ret
+}
+
+/// the same ring going the other way
+pub fn reverse_ring(ring: &[Edge]) -> Vec<Edge> {
+    ring.iter().rev().map(|e| Edge { from: e.to, to: e.from }).collect()
+}
+
+/// a polygon is stored as consecutive rings of edges.
+/// a ring ends when an edge returns to the start of the ring, or when the next edge does not follow it
+pub fn split_rings (polygon: &[Edge]) -> Vec<Vec<Edge>> {
+    let mut rings = vec![];
+    let mut cur: Vec<Edge> = vec![];
+    for edge in polygon.iter() {
+        if let Some(last) = cur.last() {
+            if (last.to - edge.from).magnitude_squared() > f32::EPSILON {
+                rings.push(std::mem::take(&mut cur));
+            }
+        }
+        cur.push(*edge);
+        if (edge.to - cur[0].from).magnitude_squared() <= f32::EPSILON {
+            rings.push(std::mem::take(&mut cur));
+        }
+    }
+    if !cur.is_empty() {
+        rings.push(cur);
+    }
+    rings
+}
+
+/// shoelace formula, accumulated in f64.
+/// with y downwards, counter-clockwise rings have negative area
+pub fn signed_area (ring: &[Edge]) -> f32 {
+    let area = ring.iter().map(|edge| {
+        edge.from.x as f64 * edge.to.y as f64 - edge.to.x as f64 * edge.from.y as f64
+    }).sum::<f64>() / 2.0;
+    area as f32
+}
+
+/// an outer ring together with the holes inside it
+#[derive(Clone, Debug)]
+pub struct RingGroup {
+    pub outer: Vec<Edge>,
+    pub holes: Vec<Vec<Edge>>
+}
+
+/// counter-clockwise rings are outer rings, clockwise rings are holes.
+/// each hole goes to the smallest outer ring containing it; holes outside every outer ring are dropped
+pub fn group_rings (polygon: &[Edge]) -> Vec<RingGroup> {
+    let (holes, outers): (Vec<_>, Vec<_>) = split_rings(polygon).into_iter()
+        .filter(|ring| ring.len() >= 3)
+        .partition(|ring| is_polygon_clockwise(ring));
+    let mut groups: Vec<RingGroup> = outers.into_iter()
+        .map(|outer| RingGroup { outer, holes: vec![] })
+        .collect();
+    for hole in holes {
+        let owner = groups.iter_mut()
+            .filter(|g| is_point_in_polygon(hole[0].from, &g.outer))
+            .min_by(|a, b| signed_area(&a.outer).abs().total_cmp(&signed_area(&b.outer).abs()));
+        if let Some(group) = owner {
+            group.holes.push(hole);
+        }
+    }
+    groups
+}
+/// every turn goes the same way and the ring winds around only once.
+/// collinear edges are allowed
+pub fn is_convex (ring: &[Edge]) -> bool {
+    let mut sign = 0.0;
+    for (e1, e2) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        let c = cross_product(&e1.get_vector(), &e2.get_vector());
+        if c.abs() <= f32::EPSILON { continue }
+        if sign == 0.0 {
+            sign = c.signum()
+        } else if c.signum() != sign {
+            return false
+        }
+    }
+    // a star like a pentagram turns the same way everywhere, but winds twice
+    let total_angle = ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(e1, e2)| get_directed_angle(e1.get_vector(), e2.get_vector()))
+        .sum::<f64>();
+    total_angle.abs() < 3.0 * std::f64::consts::PI
+}
+
+/// andrew's monotone chain. the hull is counter-clockwise with y downwards, like an outer ring.
+/// collinear points on the hull are left out
+pub fn convex_hull (points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 { return sorted }
+    let mut hull = half_hull(sorted.iter());
+    hull.extend(half_hull(sorted.iter().rev()));
+    // the chains go clockwise with y downwards
+    hull.reverse();
+    hull
+}
+
+/// one side of the hull, from the first to the last of the sorted points
+fn half_hull<'a>(points: impl Iterator<Item=&'a Vec2>) -> Vec<Vec2> {
+    let mut chain: Vec<Vec2> = vec![];
+    for p in points {
+        while chain.len() >= 2
+            && cross_product(&(chain[chain.len() - 1] - chain[chain.len() - 2]),
+                             &(p - chain[chain.len() - 2])) <= 0.0 {
+            chain.pop();
+        }
+        chain.push(*p);
+    }
+    chain.pop(); // the last point starts the other half
+    chain
+}
+
+/// convex hull of all the vertices of the polygon, as an outer ring.
+/// empty if the vertices are all on one line
+pub fn polygon_convex_hull (polygon: &[Edge]) -> Vec<Edge> {
+    let hull = convex_hull(&polygon.iter().map(|e| e.from).collect::<Vec<_>>());
+    if hull.len() < 3 { vec![] } else { point_vec_to_edges(&hull) }
+}
+
+/// a polygon being edited, with the first edge of each ring. rings are kept apart by these
+/// starts, not by where the edges are, so a vertex can be moved onto any other
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Rings {
+    pub edges: Vec<Edge>,
+    pub starts: Vec<usize>
+}
+
+impl Rings {
+    /// the rings are found with `split_rings`
+    pub fn new(edges: Vec<Edge>) -> Self {
+        let mut starts = vec![];
+        let mut start = 0;
+        for ring in split_rings(&edges) {
+            starts.push(start);
+            start += ring.len();
+        }
+        Rings { edges, starts }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn rings(&self) -> impl Iterator<Item=&[Edge]> + '_ {
+        (0..self.starts.len()).map(|ring| &self.edges[self.range(ring)])
+    }
+
+    pub fn push_ring(&mut self, ring: &[Edge]) {
+        self.starts.push(self.edges.len());
+        self.edges.extend_from_slice(ring);
+    }
+
+    /// which ring edge `i` is in
+    pub fn ring_of(&self, i: usize) -> usize {
+        assert!(i < self.edges.len(), "edge {} is not in the polygon", i);
+        self.starts.partition_point(|start| *start <= i) - 1
+    }
+
+    /// the edges of the ring that edge `i` is in
+    pub fn ring_range(&self, i: usize) -> Range<usize> {
+        self.range(self.ring_of(i))
+    }
+
+    fn range(&self, ring: usize) -> Range<usize> {
+        self.starts[ring]..self.starts.get(ring + 1).copied().unwrap_or(self.edges.len())
+    }
+
+    /// moves the start of edge `i`, which is also the end of the edge before it in the ring
+    pub fn move_vertex(&mut self, i: usize, to: Vec2) {
+        let ring = self.ring_range(i);
+        let previous = if i == ring.start { ring.end - 1 } else { i - 1 };
+        self.edges[i].from = to;
+        self.edges[previous].to = to;
+    }
+
+    /// splits edge `i` at `at`. the new vertex starts edge `i + 1`
+    pub fn insert_vertex(&mut self, i: usize, at: Vec2) {
+        let ring = self.ring_of(i);
+        let to = self.edges[i].to;
+        self.edges[i].to = at;
+        self.edges.insert(i + 1, Edge { from: at, to });
+        for start in self.starts[(ring + 1)..].iter_mut() {
+            *start += 1;
+        }
+    }
+
+    /// whether the ring goes inside one of the rings or around one without crossing any,
+    /// so that `add_ring` would nest it with them rather than put it beside them
+    pub fn nests(&self, ring: &[Edge]) -> bool {
+        !self.edges.iter().any(|a| ring.iter().any(|b| crosses(a, b)))
+            && self.rings().any(|other| contains_ring(other, ring) || contains_ring(ring, other))
+    }
+
+    /// removes the start of edge `i`, joining its neighbours. a triangle is removed as a whole,
+    /// since a ring needs 3 vertices
+    pub fn remove_vertex(&mut self, i: usize) {
+        let ring_index = self.ring_of(i);
+        let ring = self.range(ring_index);
+        if ring.len() <= 3 {
+            self.edges.drain(ring.clone());
+            self.starts.remove(ring_index);
+            for start in self.starts[ring_index..].iter_mut() {
+                *start -= ring.len();
+            }
+            return
+        }
+        let previous = if i == ring.start { ring.end - 1 } else { i - 1 };
+        self.edges[previous].to = self.edges[i].to;
+        self.edges.remove(i);
+        for start in self.starts[(ring_index + 1)..].iter_mut() {
+            *start -= 1;
+        }
+    }
+}
+
+/// why a ring cannot be added to a polygon
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RingError {
+    SelfIntersecting,
+    CrossesRing
+}
+
+impl Display for RingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RingError::SelfIntersecting => write!(f, "the ring intersects itself"),
+            RingError::CrossesRing => write!(f, "the ring crosses another ring of the polygon")
+        }
+    }
+}
+
+/// whether the edges cross each other. touching does not count
+fn crosses (a: &Edge, b: &Edge) -> bool {
+    let side = |e: &Edge, p: Vec2| cross_product(&e.get_vector(), &(p - e.from));
+    side(a, b.from) * side(a, b.to) < 0.0 && side(b, a.from) * side(b, a.to) < 0.0
+}
+
+/// whether `outer` contains a ring that does not cross it. a vertex or edge middle on none of its edges decides
+fn contains_ring (outer: &[Edge], ring: &[Edge]) -> bool {
+    let point = ring.iter().flat_map(|e| [e.from, (e.from + e.to) / 2.0])
+        .find(|p| !outer.iter().any(|e| e.has_point(p)))
+        .unwrap_or(ring[0].from);
+    is_point_in_polygon(point, outer)
+}
+
+/// how many of the other rings contain the ring
+fn nesting_depth (ring: &[Edge], others: &[&[Edge]]) -> usize {
+    others.iter().filter(|other| contains_ring(other, ring)).count()
+}
+
+/// adds a ring to a polygon, whichever way the ring was drawn. the rings may touch but not cross.
+/// every ring is then wound by how deep it is nested: counter-clockwise outside and inside holes,
+/// clockwise for holes. so a ring outside the others starts another outer ring, and one going
+/// around an outer ring turns it into a hole
+pub fn add_ring (polygon: &Rings, ring: &[Edge]) -> Result<Rings, RingError> {
+    if !is_ring_simple(ring) { return Err(RingError::SelfIntersecting) }
+    if polygon.edges.iter().any(|a| ring.iter().any(|b| crosses(a, b))) { return Err(RingError::CrossesRing) }
+    let mut rings: Vec<&[Edge]> = polygon.rings().collect();
+    rings.push(ring);
+    let mut result = Rings::default();
+    for (i, ring) in rings.iter().enumerate() {
+        let others: Vec<&[Edge]> = rings.iter().enumerate().filter(|(j, _)| *j != i).map(|(_, r)| *r).collect();
+        let depth = nesting_depth(ring, &others);
+        // clockwise rings have positive area
+        if (signed_area(ring) > 0.0) == depth.is_multiple_of(2) {
+            result.push_ring(&reverse_ring(ring));
+        } else {
+            result.push_ring(ring);
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::polygon::{add_ring, convex_hull, is_convex, is_polygon_clockwise, point_vec_to_edges, polygon_convex_hull,
+                         reverse_ring, signed_area, RingError, Rings};
+
+    fn points(coords: &[(f32, f32)]) -> Vec<Vec2> {
+        coords.iter().map(|(x, y)| Vec2 { x: *x, y: *y }).collect()
+    }
+
+    #[test]
+    fn test_is_convex() {
+        let square = point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]));
+        assert!(is_convex(&square), "fail on square");
+        let with_collinear = point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]));
+        assert!(is_convex(&with_collinear), "fail on collinear vertex");
+        let l_shape = point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]));
+        assert!(!is_convex(&l_shape), "fail on concave");
+        let pentagram = point_vec_to_edges(&points(&[(0.0, -10.0), (5.9, 8.1), (-9.5, -3.1), (9.5, -3.1), (-5.9, 8.1)]));
+        assert!(!is_convex(&pentagram), "fail on pentagram");
+    }
+
+    #[test]
+    fn test_convex_hull() {
+        let hull = convex_hull(&points(&[
+            (0.0, 0.0), (2.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 2.0), (1.0, 0.0), (0.0, 0.0)
+        ]));
+        assert_eq!(hull.len(), 4);
+        let ring = point_vec_to_edges(&hull);
+        assert!(!is_polygon_clockwise(&ring), "hull should be counter-clockwise");
+        assert_eq!(signed_area(&ring), -4.0);
+        assert_eq!(convex_hull(&points(&[(0.0, 0.0), (1.0, 1.0), (2.0, 2.0)])).len(), 2);
+        let l_shape = point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]));
+        assert_eq!(signed_area(&polygon_convex_hull(&l_shape)), -3.5);
+    }
+
+    #[test]
+    fn test_edit_vertices() {
+        let mut polygon = Rings::new(point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0)])));
+        polygon.push_ring(&point_vec_to_edges(&points(&[(5.0, 5.0), (5.0, 6.0), (6.0, 6.0)])));
+        assert_eq!(polygon.ring_range(5), 4..7);
+        polygon.move_vertex(0, Vec2 { x: -1.0, y: -1.0 });
+        assert_eq!(polygon.edges[3].to, Vec2 { x: -1.0, y: -1.0 }, "the last edge of the ring should follow");
+        polygon.insert_vertex(1, Vec2 { x: 1.0, y: 3.0 });
+        assert_eq!(polygon.edges[2].from, Vec2 { x: 1.0, y: 3.0 });
+        assert_eq!(polygon.starts, vec![0, 5]);
+        polygon.remove_vertex(2);
+        assert_eq!(polygon.edges[1].to, Vec2 { x: 2.0, y: 2.0 });
+        assert_eq!(signed_area(&polygon.edges[0..4]), -6.0);
+        polygon.remove_vertex(5);
+        assert_eq!((polygon.edges.len(), polygon.starts.len()), (4, 1), "a triangle should go as a whole");
+    }
+
+    #[test]
+    fn test_move_onto_ring_start() {
+        let square = point_vec_to_edges(&points(&[(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0)]));
+        let mut polygon = Rings::new(square.clone());
+        polygon.push_ring(&point_vec_to_edges(&points(&[(20.0, 20.0), (20.0, 21.0), (21.0, 21.0)])));
+        polygon.move_vertex(2, Vec2 { x: 0.0, y: 0.0 });
+        assert_eq!(polygon.ring_range(3), 0..4, "the ring should not split where it meets its start");
+        polygon.move_vertex(2, Vec2 { x: 10.0, y: 10.0 });
+        assert_eq!(&polygon.edges[0..4], &square[..]);
+        assert_eq!(polygon.ring_of(4), 1);
+    }
+
+    #[test]
+    fn test_add_ring() {
+        let square = |x0: f32, y0: f32, x1: f32, y1: f32| point_vec_to_edges(&points(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1)]));
+        let areas = |polygon: &Rings| polygon.rings().map(signed_area).collect::<Vec<_>>();
+        // drawn clockwise, but it is the outer ring
+        let polygon = add_ring(&Rings::default(), &square(0.0, 0.0, 4.0, 4.0)).unwrap();
+        assert_eq!(areas(&polygon), vec![-16.0]);
+        // a hole drawn counter-clockwise
+        let polygon = add_ring(&polygon, &reverse_ring(&square(1.0, 1.0, 2.0, 2.0))).unwrap();
+        assert_eq!(areas(&polygon), vec![-16.0, 1.0]);
+        // a new outer ring around everything turns the others around
+        let polygon = add_ring(&polygon, &square(-1.0, -1.0, 5.0, 5.0)).unwrap();
+        assert_eq!(areas(&polygon), vec![16.0, -1.0, -36.0]);
+
+        let bow_tie = point_vec_to_edges(&points(&[(0.0, 0.0), (1.0, 1.0), (1.0, 0.0), (0.0, 1.0)]));
+        assert_eq!(add_ring(&Rings::default(), &bow_tie), Err(RingError::SelfIntersecting));
+        assert_eq!(add_ring(&polygon, &square(4.0, 4.0, 6.0, 6.0)), Err(RingError::CrossesRing));
+        // another outer ring
+        let apart = add_ring(&polygon, &square(7.0, 7.0, 8.0, 8.0)).unwrap();
+        assert_eq!(areas(&apart)[3], -1.0);
+        // touching the outer ring is fine
+        assert!(add_ring(&polygon, &square(-1.0, -1.0, 0.0, 0.0)).is_ok());
+    }
+
+    #[test]
+    fn test_nests() {
+        let square = |x0: f32, y0: f32, x1: f32, y1: f32| point_vec_to_edges(&points(&[(x0, y0), (x1, y0), (x1, y1), (x0, y1)]));
+        let polygon = add_ring(&Rings::default(), &square(0.0, 0.0, 4.0, 4.0)).unwrap();
+        let polygon = add_ring(&polygon, &square(1.0, 1.0, 2.0, 2.0)).unwrap();
+        assert!(polygon.nests(&square(2.5, 2.5, 3.5, 3.5)));
+        assert!(polygon.nests(&square(1.2, 1.2, 1.8, 1.8)));
+        assert!(polygon.nests(&square(-1.0, -1.0, 5.0, 5.0)));
+        // sharing an edge with the outer ring from inside
+        assert!(polygon.nests(&square(0.0, 3.0, 1.0, 4.0)));
+        assert!(!polygon.nests(&square(3.0, 3.0, 6.0, 6.0)));
+        assert!(!polygon.nests(&square(5.0, 5.0, 6.0, 6.0)));
+        assert!(!Rings::default().nests(&square(0.0, 0.0, 1.0, 1.0)));
+    }
+}
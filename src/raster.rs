@@ -0,0 +1,186 @@
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::{resolve_fill, FillRule};
+use crate::edge::Edge;
+use crate::polygon::split_rings;
+
+/// how much of each pixel a polygon covers, from 0 to 255. rows from top to bottom
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoverageMask {
+    pub width: u32,
+    pub height: u32,
+    pub coverage: Vec<u8>
+}
+
+impl CoverageMask {
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        self.coverage[y as usize * self.width as usize + x as usize]
+    }
+
+    /// the covered area in pixels
+    pub fn area(&self) -> f32 {
+        self.coverage.iter().map(|c| *c as f32).sum::<f32>() / 255.0
+    }
+}
+
+/// the exact area of each pixel inside the polygon under `rule`, in a `width` × `height` mask.
+/// coordinates are in pixels. overlapping and self-intersecting rings are resolved to a valid
+/// polygon first, so that the areas of its edges can simply be added up
+pub fn rasterize(polygon: &[Edge], rule: FillRule, width: u32, height: u32) -> CoverageMask {
+    let resolved = resolve_fill(&split_rings(polygon), rule);
+    // two spare columns take what lies right of the mask
+    let stride = width as usize + 2;
+    let mut accumulation = vec![0.0f32; stride * height as usize];
+    for edge in resolved.iter() {
+        for piece in clamp_to_columns(edge, width as f32) {
+            accumulate_edge(&mut accumulation, stride, height, piece.from, piece.to);
+        }
+    }
+    let mut coverage = Vec::with_capacity(width as usize * height as usize);
+    for row in accumulation.chunks(stride) {
+        let mut sum = 0.0;
+        for a in &row[..width as usize] {
+            sum += a;
+            coverage.push((sum.abs().min(1.0) * 255.0).round() as u8);
+        }
+    }
+    CoverageMask { width, height, coverage }
+}
+
+/// the edge split where it crosses x = 0 and x = `width`, with the parts outside moved onto those
+/// lines. a part left of the mask covers the whole of every row it spans, just like one on the left
+/// side, and a part on the right covers nothing
+fn clamp_to_columns(edge: &Edge, width: f32) -> Vec<Edge> {
+    let v = edge.get_vector();
+    let mut ts: Vec<f32> = [0.0, width].iter()
+        .filter(|_| v.x != 0.0)
+        .map(|x| (x - edge.from.x) / v.x)
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .collect();
+    ts.sort_by(|a, b| a.total_cmp(b));
+    let points: Vec<Vec2> = std::iter::once(0.0).chain(ts).chain(std::iter::once(1.0))
+        .map(|t| edge.from + v * t)
+        .map(|p| Vec2::new(p.x.clamp(0.0, width), p.y))
+        .collect();
+    points.windows(2).map(|p| Edge { from: p[0], to: p[1] }).collect()
+}
+
+/// adds the signed area between the edge and the right end of each row it crosses, spread over
+/// the pixels so that summing a row from the left gives each pixel's coverage. x must be inside the mask
+fn accumulate_edge(accumulation: &mut [f32], stride: usize, height: u32, p0: Vec2, p1: Vec2) {
+    if p0.y == p1.y { return }
+    let (direction, p0, p1) = if p0.y < p1.y { (1.0, p0, p1) } else { (-1.0, p1, p0) };
+    let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+    let first_row = p0.y.max(0.0).floor() as usize;
+    let end_row = (p1.y.ceil().max(0.0) as usize).min(height as usize);
+    for y in first_row..end_row {
+        let row = &mut accumulation[(y * stride)..((y + 1) * stride)];
+        let top = (y as f32).max(p0.y);
+        let bottom = ((y + 1) as f32).min(p1.y);
+        let dy = bottom - top;
+        if dy <= 0.0 { continue }
+        let d = dy * direction;
+        let x_top = p0.x + (top - p0.y) * dxdy;
+        let x_bottom = p0.x + (bottom - p0.y) * dxdy;
+        let (x0, x1) = if x_top < x_bottom { (x_top, x_bottom) } else { (x_bottom, x_top) };
+        let x0_floor = x0.floor();
+        let x0i = x0_floor as usize;
+        let x1_ceil = x1.ceil();
+        let x1i = x1_ceil as usize;
+        if x1i <= x0i + 1 {
+            // within one pixel: the part of it right of the edge's middle is covered
+            let middle = 0.5 * (x_top + x_bottom) - x0_floor;
+            row[x0i] += d * (1.0 - middle);
+            row[x0i + 1] += d * middle;
+        } else {
+            // across several pixels: a triangle in the first, a trapezoid in each of the others
+            let s = (x1 - x0).recip();
+            let x0f = x0 - x0_floor;
+            let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+            let x1f = x1 - x1_ceil + 1.0;
+            let am = 0.5 * s * x1f * x1f;
+            row[x0i] += d * a0;
+            if x1i == x0i + 2 {
+                row[x0i + 1] += d * (1.0 - a0 - am);
+            } else {
+                let a1 = s * (1.5 - x0f);
+                row[x0i + 1] += d * (a1 - a0);
+                for a in &mut row[(x0i + 2)..(x1i - 1)] {
+                    *a += d * s;
+                }
+                let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                row[x1i - 1] += d * (1.0 - a2 - am);
+            }
+            row[x1i] += d * am;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use speedy2d::dimen::Vec2;
+
+    use crate::boolean::FillRule;
+    use crate::fixtures::rect;
+    use crate::polygon::{point_vec_to_edges, reverse_ring, signed_area};
+    use crate::raster::rasterize;
+
+    #[test]
+    fn test_pixel_coverage() {
+        let mask = rasterize(&rect(1.0, 1.0, 3.0, 3.0), FillRule::NonZero, 4, 4);
+        assert_eq!(mask.coverage, [
+            0, 0, 0, 0,
+            0, 255, 255, 0,
+            0, 255, 255, 0,
+            0, 0, 0, 0
+        ]);
+        let mask = rasterize(&rect(0.5, 0.5, 2.5, 2.5), FillRule::NonZero, 3, 3);
+        assert_eq!(mask.coverage, [
+            64, 128, 64,
+            128, 255, 128,
+            64, 128, 64
+        ]);
+        let triangle = point_vec_to_edges(&[Vec2::new(0.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(1.0, 1.0)]);
+        assert_eq!(rasterize(&triangle, FillRule::NonZero, 1, 1).coverage, [128]);
+        // the parts outside are cut off
+        let mask = rasterize(&rect(-2.0, -2.0, 2.0, 2.0), FillRule::NonZero, 4, 4);
+        assert_eq!((mask.get(1, 1), mask.get(2, 1), mask.get(0, 2)), (255, 0, 0));
+        assert_eq!(mask.area(), 4.0);
+    }
+
+    #[test]
+    fn test_areas() {
+        let circle: Vec<Vec2> = (0..64).map(|i| {
+            let a = -(i as f32) * 2.0 * PI / 64.0;
+            Vec2::new(16.0 + 10.3 * a.cos(), 16.0 + 10.3 * a.sin())
+        }).collect();
+        let circle = point_vec_to_edges(&circle);
+        let mask = rasterize(&circle, FillRule::NonZero, 32, 32);
+        // each pixel is rounded to 1/255
+        assert!((mask.area() - signed_area(&circle).abs()).abs() < 0.2, "{}", mask.area());
+
+        let diamond = point_vec_to_edges(&[Vec2::new(5.0, 0.3), Vec2::new(0.3, 5.0), Vec2::new(5.0, 9.7), Vec2::new(9.7, 5.0)]);
+        let mask = rasterize(&diamond, FillRule::EvenOdd, 10, 10);
+        assert!((mask.area() - 2.0 * 4.7 * 4.7).abs() < 0.1, "{}", mask.area());
+
+        let mut with_hole = rect(0.0, 0.0, 10.0, 10.0);
+        with_hole.extend(reverse_ring(&rect(2.5, 2.5, 7.5, 7.5)));
+        let mask = rasterize(&with_hole, FillRule::NonZero, 10, 10);
+        assert!((mask.area() - 75.0).abs() < 0.05, "{}", mask.area());
+        assert_eq!((mask.get(5, 5), mask.get(2, 5), mask.get(1, 5)), (0, 128, 255));
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        // two squares wound the same way, overlapping in a 2 × 2 square
+        let mut polygon = rect(0.0, 0.0, 4.0, 4.0);
+        polygon.extend(rect(2.0, 2.0, 6.0, 6.0));
+        let even_odd = rasterize(&polygon, FillRule::EvenOdd, 8, 8);
+        let non_zero = rasterize(&polygon, FillRule::NonZero, 8, 8);
+        assert_eq!(even_odd.area(), 32.0 - 8.0);
+        assert_eq!(non_zero.area(), 32.0 - 4.0);
+        assert_eq!((even_odd.get(3, 3), non_zero.get(3, 3)), (0, 255));
+    }
+}
@@ -0,0 +1,365 @@
+use std::ops::Range;
+use std::path::Path;
+
+use speedy2d::color::Color;
+use speedy2d::dimen::Vec2;
+
+use crate::boolean::{BooleanOp, FillRule};
+use crate::edge::Edge;
+
+/// the colours of the viewer, also used for rendered images
+pub const SUBJECT_COLOR: Color = Color::RED;
+pub const CLIPPING_COLOR: Color = Color::GREEN;
+pub const RESULT_COLOR: Color = Color::BLUE;
+pub const RESULT_FILL_COLOR: Color = Color::CYAN;
+/// how opaque the result's fill is, so that the subject and clipping polygons show through
+pub const RESULT_FILL_ALPHA: f32 = 0.5;
+pub const GRID_COLOR: Color = Color::LIGHT_GRAY;
+pub const EDGE_THICKNESS: f32 = 3.0;
+
+/// the translucent fill of an operation's result: cyan for the intersection, yellow for the union,
+/// magenta for the difference and orange for XOR
+pub fn result_fill_color(op: BooleanOp) -> Color {
+    let color = match op {
+        BooleanOp::Intersection => RESULT_FILL_COLOR,
+        BooleanOp::Union => Color::YELLOW,
+        BooleanOp::Difference => Color::MAGENTA,
+        BooleanOp::Xor => Color::from_rgb(1.0, 0.6, 0.0)
+    };
+    Color::from_rgba(color.r(), color.g(), color.b(), RESULT_FILL_ALPHA)
+}
+
+const MIN_SCALE: f32 = 1e-4;
+const MAX_SCALE: f32 = 1e4;
+
+/// the transform between world coordinates, where the polygons are, and pixels on screen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    /// pixels per unit
+    pub scale: f32,
+    /// the point drawn at the top left corner
+    pub origin: Vec2
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Camera { scale: 1.0, origin: Vec2::ZERO }
+    }
+}
+
+impl Camera {
+    /// showing all the polygons in the middle of a view of the given size,
+    /// leaving a margin of a tenth of the view around them
+    pub fn fit(polygons: &[&[Edge]], width: u32, height: u32) -> Camera {
+        let points: Vec<Vec2> = polygons.iter().flat_map(|p| p.iter()).map(|e| e.from).collect();
+        if points.is_empty() { return Camera::default() }
+        let (min, max) = points.iter().fold((points[0], points[0]), |(min, max), p| (
+            Vec2::new(min.x.min(p.x), min.y.min(p.y)),
+            Vec2::new(max.x.max(p.x), max.y.max(p.y))
+        ));
+        let size = max - min;
+        let scale = (width as f32 / size.x).min(height as f32 / size.y) * 0.8;
+        let scale = if scale.is_finite() && scale > 0.0 { scale.clamp(MIN_SCALE, MAX_SCALE) } else { 1.0 };
+        let centre = (min + max) / 2.0;
+        Camera { scale, origin: centre - Vec2::new(width as f32, height as f32) / (2.0 * scale) }
+    }
+
+    pub fn to_screen(self, p: Vec2) -> Vec2 {
+        (p - self.origin) * self.scale
+    }
+
+    pub fn to_world(self, p: Vec2) -> Vec2 {
+        p / self.scale + self.origin
+    }
+
+    /// zooms by `factor`, keeping the point under `screen` in place
+    pub fn zoom_at(&mut self, screen: Vec2, factor: f32) {
+        let anchor = self.to_world(screen);
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.origin = anchor - screen / self.scale;
+    }
+
+    /// moves the view so that the picture moves by `delta` pixels
+    pub fn pan(&mut self, delta: Vec2) {
+        self.origin = self.origin - delta / self.scale;
+    }
+
+    /// where grid lines `spacing` apart cross a view of the given size: x then y, in pixels.
+    /// lines go through the centres of pixels, so that each of them is one pixel wide
+    pub fn grid_lines(self, spacing: f32, width: u32, height: u32) -> (Vec<f32>, Vec<f32>) {
+        let lines = |origin: f32, size: u32| -> Vec<f32> {
+            let first = (origin / spacing).ceil() as i64;
+            (first..).map(|i| ((i as f32 * spacing - origin) * self.scale).floor() + 0.5)
+                .take_while(|p| *p < size as f32)
+                .collect()
+        };
+        (lines(self.origin.x, width), lines(self.origin.y, height))
+    }
+}
+
+/// how a scene is laid out in an image
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub width: u32,
+    pub height: u32,
+    pub camera: Camera,
+    /// distance between grid lines, or no grid
+    pub grid: Option<f32>
+}
+
+impl Default for RenderOptions {
+    /// the same view as the viewer's window
+    fn default() -> Self {
+        RenderOptions { width: 1600, height: 900, camera: Camera::default(), grid: Some(100.0) }
+    }
+}
+
+impl RenderOptions {
+    /// an image of the given size showing all the polygons, see `Camera::fit`
+    pub fn fit(polygons: &[&[Edge]], width: u32, height: u32) -> RenderOptions {
+        RenderOptions { width, height, camera: Camera::fit(polygons, width, height), ..RenderOptions::default() }
+    }
+}
+
+/// a run of pixels on one row
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub y: u32,
+    pub x: Range<u32>
+}
+
+/// the runs of pixels whose centres are inside the polygon under `rule`, row by row, in a
+/// `width` × `height` area. coordinates are in pixels.
+/// an active edge table keeps the edges crossing the current row, so each row only looks at those
+pub fn scanline_spans(polygon: &[Edge], rule: FillRule, width: u32, height: u32) -> Vec<Span> {
+    // edges by their top end, leaving out horizontal ones which never cross a row's centre
+    let mut edge_table: Vec<(f32, f32, &Edge)> = polygon.iter()
+        .filter(|e| e.from.y != e.to.y)
+        .map(|e| (e.from.y.min(e.to.y), e.from.y.max(e.to.y), e))
+        .collect();
+    edge_table.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut spans = vec![];
+    let Some(first) = edge_table.first() else { return spans };
+    let max_y = edge_table.iter().map(|e| e.1).fold(f32::MIN, f32::max);
+    let first_row = (first.0 - 0.5).ceil().max(0.0) as u32;
+    let end_row = ((max_y - 0.5).ceil().min(height as f32)).max(0.0) as u32;
+    let mut next = 0;
+    let mut active: Vec<(f32, f32, &Edge)> = vec![];
+    let mut crossings: Vec<(f32, i32)> = vec![];
+    for y in first_row..end_row {
+        let sy = y as f32 + 0.5;
+        // an edge covers the centres from its top, inclusive, to its bottom
+        while next < edge_table.len() && edge_table[next].0 <= sy {
+            active.push(edge_table[next]);
+            next += 1;
+        }
+        active.retain(|(_, bottom, _)| *bottom > sy);
+        crossings.clear();
+        crossings.extend(active.iter().map(|(_, _, e)| (
+            e.from.x + (sy - e.from.y) * (e.to.x - e.from.x) / (e.to.y - e.from.y),
+            if e.to.y > e.from.y { 1 } else { -1 }
+        )));
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let mut winding = 0;
+        for pair in crossings.windows(2) {
+            winding += pair[0].1;
+            if !rule.fills(winding) { continue }
+            let from = (pair[0].0 - 0.5).ceil().clamp(0.0, width as f32) as u32;
+            let to = (pair[1].0 - 0.5).ceil().clamp(0.0, width as f32) as u32;
+            if from >= to { continue }
+            // runs that touch, from rings sharing an edge, are one
+            match spans.last_mut() {
+                Some(Span { y: last_y, x }) if *last_y == y && x.end == from => x.end = to,
+                _ => spans.push(Span { y, x: from..to })
+            }
+        }
+    }
+    spans
+}
+
+/// an RGBA image drawn on the CPU, so no display or GPU is needed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    /// rows from top to bottom, 4 bytes per pixel
+    pub pixels: Vec<u8>
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let pixel = [background.r(), background.g(), background.b(), background.a()].map(|c| (c * 255.0).round() as u8);
+        let len = (width as usize).checked_mul(height as usize).expect("image size overflows");
+        Image { width, height, pixels: pixel.repeat(len) }
+    }
+
+    /// where the pixel's bytes start
+    fn index(&self, x: u32, y: u32) -> usize {
+        (y as usize * self.width as usize + x as usize) * 4
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.index(x, y);
+        self.pixels[i..(i + 4)].try_into().unwrap()
+    }
+
+    /// blends `color` over the pixel
+    fn blend(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.index(x, y);
+        let a = color.a();
+        for (k, c) in [color.r(), color.g(), color.b()].iter().enumerate() {
+            self.pixels[i + k] = (c * 255.0 * a + self.pixels[i + k] as f32 * (1.0 - a)).round() as u8;
+        }
+        self.pixels[i + 3] = (a * 255.0 + self.pixels[i + 3] as f32 * (1.0 - a)).round() as u8;
+    }
+
+    /// fills the pixels whose centres are inside the polygon, by the non-zero rule
+    /// so that holes stay empty. coordinates are in pixels
+    pub fn fill_polygon(&mut self, polygon: &[Edge], color: Color) {
+        for span in scanline_spans(polygon, FillRule::NonZero, self.width, self.height) {
+            for x in span.x {
+                self.blend(x, span.y, color);
+            }
+        }
+    }
+
+    /// a line with square ends, like `Graphics2D::draw_line`
+    pub fn draw_line(&mut self, from: Vec2, to: Vec2, thickness: f32, color: Color) {
+        let v = to - from;
+        let Some(direction) = v.normalize() else { return };
+        let n = Vec2::new(-direction.y, direction.x) * (thickness / 2.0);
+        let corners = [from + n, to + n, to - n, from - n];
+        let quad: Vec<Edge> = (0..4).map(|i| Edge { from: corners[i], to: corners[(i + 1) % 4] }).collect();
+        self.fill_polygon(&quad, color);
+    }
+
+    pub fn encode_png(&self) -> Vec<u8> {
+        let mut out = vec![];
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        // writing to memory only fails if the data does not match the size
+        let mut writer = encoder.write_header().expect("PNG header");
+        writer.write_image_data(&self.pixels).expect("PNG data");
+        drop(writer);
+        out
+    }
+
+    pub fn save_png(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.encode_png())
+    }
+}
+
+/// draws a scene the way the viewer shows a finished clip: grid, red subject, green clipping polygon,
+/// and the result of `op` with a blue outline, filled in the operation's colour so the others show through
+pub fn render_scene(subject: &[Edge], clipping: &[Edge], result: &[Edge], op: BooleanOp, options: &RenderOptions) -> Image {
+    let mut image = Image::new(options.width, options.height, Color::WHITE);
+    let camera = options.camera;
+    if let Some(spacing) = options.grid.filter(|s| *s * camera.scale >= 2.0) {
+        let (w, h) = (options.width as f32, options.height as f32);
+        let (xs, ys) = camera.grid_lines(spacing, options.width, options.height);
+        for x in xs {
+            image.draw_line(Vec2::new(x, 0.0), Vec2::new(x, h), 1.0, GRID_COLOR);
+        }
+        for y in ys {
+            image.draw_line(Vec2::new(0.0, y), Vec2::new(w, y), 1.0, GRID_COLOR);
+        }
+    }
+    let to_pixels = |polygon: &[Edge]| -> Vec<Edge> {
+        polygon.iter().map(|e| Edge { from: camera.to_screen(e.from), to: camera.to_screen(e.to) }).collect()
+    };
+    let draw_polygon = |image: &mut Image, polygon: &[Edge], color: Color| {
+        for e in to_pixels(polygon) {
+            image.draw_line(e.from, e.to, EDGE_THICKNESS, color);
+        }
+    };
+    draw_polygon(&mut image, subject, SUBJECT_COLOR);
+    draw_polygon(&mut image, clipping, CLIPPING_COLOR);
+    image.fill_polygon(&to_pixels(result), result_fill_color(op));
+    draw_polygon(&mut image, result, RESULT_COLOR);
+    image
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::color::Color;
+    use speedy2d::dimen::Vec2;
+
+    use crate::boolean::{BooleanOp, FillRule};
+    use crate::fixtures::rect;
+    use crate::polygon::reverse_ring;
+    use crate::render::{render_scene, scanline_spans, Camera, Image, RenderOptions, Span};
+
+    const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+    #[test]
+    fn test_fill() {
+        let mut image = Image::new(10, 10, Color::WHITE);
+        let mut polygon = rect(1.0, 1.0, 9.0, 9.0);
+        polygon.extend(reverse_ring(&rect(3.0, 3.0, 7.0, 7.0)));
+        image.fill_polygon(&polygon, Color::BLACK);
+        let filled = image.pixels.chunks(4).filter(|p| *p != WHITE).count();
+        assert_eq!(filled, 64 - 16);
+        assert_eq!(image.pixel(5, 5), WHITE, "holes should stay empty");
+        assert_eq!(image.pixel(1, 1), [0, 0, 0, 255]);
+        image.fill_polygon(&rect(0.0, 0.0, 1.0, 1.0), Color::from_rgba(0.0, 0.0, 0.0, 0.5));
+        assert_eq!(image.pixel(0, 0), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn test_scanline_spans() {
+        // two squares overlapping on rows 2 and 3, the second one wound the other way
+        let mut polygon = rect(0.0, 0.0, 4.0, 4.0);
+        polygon.extend(reverse_ring(&rect(2.0, 2.0, 6.0, 6.0)));
+        let spans = scanline_spans(&polygon, FillRule::EvenOdd, 5, 10);
+        assert_eq!(spans[2], Span { y: 2, x: 0..2 });
+        assert_eq!(spans[3], Span { y: 2, x: 4..5 }, "clipped to the width");
+        assert_eq!(spans.len(), 2 + 2 * 2 + 2);
+        let spans = scanline_spans(&polygon, FillRule::NonZero, 10, 10);
+        assert_eq!(spans[2], Span { y: 2, x: 0..2 }, "the windings cancel out");
+        let spans = scanline_spans(&rect(0.0, 0.0, 4.0, 4.0), FillRule::NonZero, 10, 10);
+        assert_eq!(spans, (0..4).map(|y| Span { y, x: 0..4 }).collect::<Vec<_>>());
+        assert!(scanline_spans(&rect(0.0, -5.0, 4.0, -1.0), FillRule::NonZero, 10, 10).is_empty());
+    }
+
+    #[test]
+    fn test_scene() {
+        let subject = rect(100.0, 100.0, 300.0, 300.0);
+        let clipping = rect(200.0, 200.0, 400.0, 400.0);
+        let result = rect(200.0, 200.0, 300.0, 300.0);
+        let options = RenderOptions { width: 500, height: 500, ..RenderOptions::default() };
+        let image = render_scene(&subject, &clipping, &result, BooleanOp::Intersection, &options);
+        assert_eq!(image.pixel(250, 250), [128, 255, 255, 255], "result should be filled in translucent cyan");
+        assert_eq!(image.pixel(150, 100), [255, 0, 0, 255]);
+        assert_eq!(image.pixel(400, 350), [0, 255, 0, 255]);
+        assert_eq!(image.pixel(300, 250), [0, 0, 255, 255]);
+        assert_eq!(image.pixel(50, 200), [191, 191, 191, 255], "grid lines every 100 pixels");
+        assert_eq!(image.pixel(50, 50), WHITE);
+        let png = image.encode_png();
+        let (info, _) = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        assert_eq!((info.width, info.height), (500, 500));
+    }
+
+    #[test]
+    fn test_fit() {
+        let small = rect(0.0, 0.0, 1.0, 2.0);
+        let options = RenderOptions::fit(&[&small], 100, 100);
+        assert_eq!(options.camera.scale, 40.0);
+        assert_eq!(options.camera.origin, Vec2::new(-0.75, -0.25));
+    }
+
+    #[test]
+    fn test_camera() {
+        let mut camera = Camera { scale: 2.0, origin: Vec2::new(10.0, 20.0) };
+        let p = Vec2::new(15.0, 30.0);
+        assert_eq!(camera.to_screen(p), Vec2::new(10.0, 20.0));
+        assert_eq!(camera.to_world(camera.to_screen(p)), p);
+        camera.zoom_at(Vec2::new(10.0, 20.0), 4.0);
+        assert_eq!(camera.scale, 8.0);
+        assert_eq!(camera.to_screen(p), Vec2::new(10.0, 20.0), "the point under the cursor should stay put");
+        camera.pan(Vec2::new(8.0, -16.0));
+        assert_eq!(camera.to_screen(p), Vec2::new(18.0, 4.0));
+        let (xs, ys) = Camera { scale: 0.5, origin: Vec2::new(-50.0, 0.0) }.grid_lines(100.0, 100, 60);
+        assert_eq!((xs, ys), (vec![25.5, 75.5], vec![0.5, 50.5]));
+    }
+}
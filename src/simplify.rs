@@ -0,0 +1,208 @@
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::polygon::{is_point_in_polygon, point_vec_to_edges, split_rings};
+use crate::validation::{edges_cross, validate_polygon, ValidationError};
+use crate::vec::{cross_product, dist_to_line, inner_product};
+
+/// douglas-peucker. a vertex is dropped if it lies within `tolerance` of the edge replacing it.
+/// every ring keeps at least 3 vertices, and an edge is only shortened
+/// if the shortcut neither crosses another edge nor passes over another vertex,
+/// so rings stay simple and holes stay inside their shell
+pub fn simplify_douglas_peucker (polygon: &[Edge], tolerance: f32) -> Result<Vec<Edge>, ValidationError> {
+    validate_polygon(polygon)?;
+    let mut rings = Rings::new(polygon);
+    for r in 0..rings.points.len() {
+        let points = &rings.points[r];
+        // split the ring at the vertex farthest from the first one
+        let far = (0..points.len())
+            .max_by(|i, j| (points[*i] - points[0]).magnitude_squared()
+                .total_cmp(&(points[*j] - points[0]).magnitude_squared()))
+            .unwrap();
+        douglas_peucker(&mut rings, r, 0, far, tolerance);
+        douglas_peucker(&mut rings, r, far, 0, tolerance);
+    }
+    Ok(rings.to_polygon())
+}
+
+/// visvalingam-whyatt. the vertex forming the smallest triangle with its neighbours is dropped
+/// as long as that area is below `min_area`, with the same guarantees as `simplify_douglas_peucker`
+pub fn simplify_visvalingam (polygon: &[Edge], min_area: f32) -> Result<Vec<Edge>, ValidationError> {
+    validate_polygon(polygon)?;
+    let mut rings = Rings::new(polygon);
+    // vertices that cannot go without breaking the topology, until one of their neighbours goes
+    let mut blocked: Vec<Vec<bool>> = rings.points.iter().map(|r| vec![false; r.len()]).collect();
+    loop {
+        let mut best: Option<(f32, usize, usize)> = None;
+        for (r, ring_blocked) in blocked.iter().enumerate() {
+            if rings.kept_count(r) <= 3 { continue }
+            for i in 0..rings.points[r].len() {
+                if !rings.keep[r][i] || ring_blocked[i] { continue }
+                let (prev, next) = (rings.prev_kept(r, i), rings.next_kept(r, i));
+                let p = &rings.points[r];
+                let area = cross_product(&(p[i] - p[prev]), &(p[next] - p[i])).abs() / 2.0;
+                if best.is_none_or(|(a, _, _)| area < a) {
+                    best = Some((area, r, i));
+                }
+            }
+        }
+        let Some((area, r, i)) = best else { break };
+        if area > min_area { break }
+        let (prev, next) = (rings.prev_kept(r, i), rings.next_kept(r, i));
+        if rings.can_shortcut(r, prev, next) {
+            rings.keep[r][i] = false;
+            blocked[r][prev] = false;
+            blocked[r][next] = false;
+        } else {
+            blocked[r][i] = true;
+        }
+    }
+    Ok(rings.to_polygon())
+}
+
+fn dist_to_segment(point: Vec2, from: Vec2, to: Vec2) -> f32 {
+    let line = to - from;
+    let t = inner_product(&(point - from), &line) / line.magnitude_squared();
+    if line.magnitude_squared() <= f32::EPSILON || t <= 0.0 {
+        (point - from).magnitude()
+    } else if t >= 1.0 {
+        (point - to).magnitude()
+    } else {
+        dist_to_line(&line, &(point - from))
+    }
+}
+
+fn douglas_peucker(rings: &mut Rings, r: usize, from: usize, to: usize, tolerance: f32) {
+    let n = rings.points[r].len();
+    let steps = (to + n - from) % n;
+    if steps < 2 { return }
+    let p = &rings.points[r];
+    let (far, dist) = (1..steps)
+        .map(|k| (from + k) % n)
+        .map(|i| (i, dist_to_segment(p[i], p[from], p[to])))
+        .max_by(|(_, d1), (_, d2)| d1.total_cmp(d2))
+        .unwrap();
+    if dist <= tolerance && rings.can_shortcut(r, from, to) {
+        (1..steps).for_each(|k| rings.keep[r][(from + k) % n] = false);
+        return
+    }
+    douglas_peucker(rings, r, from, far, tolerance);
+    douglas_peucker(rings, r, far, to, tolerance);
+}
+
+/// the rings being simplified. dropped vertices stay in `points` but are no longer kept
+struct Rings {
+    points: Vec<Vec<Vec2>>,
+    keep: Vec<Vec<bool>>
+}
+
+impl Rings {
+    fn new(polygon: &[Edge]) -> Self {
+        let points: Vec<Vec<Vec2>> = split_rings(polygon).iter()
+            .map(|ring| ring.iter().map(|e| e.from).collect())
+            .collect();
+        let keep = points.iter().map(|r| vec![true; r.len()]).collect();
+        Rings { points, keep }
+    }
+
+    fn kept_count(&self, r: usize) -> usize {
+        self.keep[r].iter().filter(|k| **k).count()
+    }
+
+    fn next_kept(&self, r: usize, i: usize) -> usize {
+        let n = self.points[r].len();
+        (1..n).map(|k| (i + k) % n).find(|j| self.keep[r][*j]).unwrap_or(i)
+    }
+
+    fn prev_kept(&self, r: usize, i: usize) -> usize {
+        let n = self.points[r].len();
+        (1..n).map(|k| (i + n - k) % n).find(|j| self.keep[r][*j]).unwrap_or(i)
+    }
+
+    /// kept vertices going forward from `from` to `to`, both included
+    fn span(&self, r: usize, from: usize, to: usize) -> Vec<usize> {
+        let n = self.points[r].len();
+        let steps = (to + n - from) % n;
+        (0..=steps).map(|k| (from + k) % n).filter(|i| self.keep[r][*i]).collect()
+    }
+
+    /// whether the kept vertices strictly between `from` and `to` can be replaced by a single edge
+    fn can_shortcut(&self, r: usize, from: usize, to: usize) -> bool {
+        let span = self.span(r, from, to);
+        let p = &self.points[r];
+        if self.kept_count(r) + 2 < span.len() + 3 { return false }
+        let shortcut = Edge { from: p[from], to: p[to] };
+        if shortcut.get_vector().magnitude_squared() <= f32::EPSILON { return false }
+        let cut_off: Vec<Vec2> = span.iter().map(|i| p[*i]).collect();
+        let cut_off = point_vec_to_edges(&cut_off);
+        let (min, max) = cut_off.iter().fold((cut_off[0].from, cut_off[0].from), |(min, max), e| (
+            Vec2::new(min.x.min(e.from.x), min.y.min(e.from.y)),
+            Vec2::new(max.x.max(e.from.x), max.y.max(e.from.y))
+        ));
+        for (q, ring) in self.points.iter().enumerate() {
+            for i in (0..ring.len()).filter(|i| self.keep[q][*i]) {
+                let in_span = q == r && span.contains(&i);
+                // the shortcut may not cross the other edges ...
+                let edge = Edge { from: ring[i], to: ring[self.next_kept(q, i)] };
+                if (!in_span || i == to) && edges_cross(&shortcut, &edge) {
+                    return false
+                }
+                if in_span { continue }
+                // ... nor cut off another vertex, which would take a whole ring with it
+                let v = ring[i];
+                let in_box = v.x >= min.x && v.x <= max.x && v.y >= min.y && v.y <= max.y;
+                if in_box && is_point_in_polygon(v, &cut_off) {
+                    return false
+                }
+            }
+        }
+        true
+    }
+
+    fn to_polygon(&self) -> Vec<Edge> {
+        self.points.iter().zip(self.keep.iter()).flat_map(|(points, keep)| {
+            let kept: Vec<Vec2> = points.iter().zip(keep.iter()).filter(|(_, k)| **k).map(|(p, _)| *p).collect();
+            point_vec_to_edges(&kept)
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::ring;
+    use crate::polygon::split_rings;
+    use crate::simplify::{simplify_douglas_peucker, simplify_visvalingam};
+    use crate::validation::{validate_polygon, ValidationError};
+
+    #[test]
+    fn test_noisy_square() {
+        let mut points = vec![];
+        for i in 0..10 {
+            points.push((if i % 2 == 0 { 0.0 } else { 0.05 }, i as f32));
+        }
+        for i in 0..10 {
+            points.push((i as f32, if i % 2 == 0 { 10.0 } else { 10.05 }));
+        }
+        points.extend([(10.0, 10.0), (10.0, 0.0), (5.0, 0.03)]);
+        let noisy = ring(&points);
+        assert_eq!(simplify_douglas_peucker(&noisy, 0.1).unwrap().len(), 4);
+        assert_eq!(simplify_visvalingam(&noisy, 0.5).unwrap().len(), 4);
+        assert_eq!(simplify_douglas_peucker(&noisy, 0.01).unwrap().len(), noisy.len());
+    }
+
+    #[test]
+    fn test_topology() {
+        // the bump at (5, -1) would be cut off together with the hole inside it
+        let outer = ring(&[(0.0, 0.0), (0.0, 10.0), (10.0, 10.0), (10.0, 0.0), (5.0, -1.0)]);
+        let hole = ring(&[(4.5, -0.3), (5.0, -0.7), (5.5, -0.3)]);
+        assert_eq!(simplify_douglas_peucker(&outer, 2.0).unwrap().len(), 4);
+        assert_eq!(simplify_visvalingam(&outer, 10.0).unwrap().len(), 4);
+        let polygon = [outer, hole].concat();
+        for res in [simplify_douglas_peucker(&polygon, 2.0).unwrap(), simplify_visvalingam(&polygon, 10.0).unwrap()] {
+            assert_eq!(validate_polygon(&res), Ok(()));
+            assert_eq!(split_rings(&res)[0].len(), 5);
+        }
+        let bow_tie = ring(&[(0.0, 0.0), (1.0, 1.0), (1.0, 0.0), (0.0, 1.0)]);
+        assert_eq!(simplify_douglas_peucker(&bow_tie, 1.0).err(), Some(ValidationError::SelfIntersection { ring: 0 }));
+    }
+}
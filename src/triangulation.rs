@@ -0,0 +1,209 @@
+use speedy2d::dimen::{Vec2, Vector2};
+
+use crate::edge::Edge;
+use crate::polygon::{group_rings, signed_area, RingGroup};
+use crate::vec::cross_product;
+
+/// triangles as an index buffer, three indices per triangle.
+/// triangles are counter-clockwise with y downwards, the same as outer rings
+#[derive(Clone, Debug, Default)]
+pub struct Triangulation {
+    pub vertices: Vec<Vec2>,
+    pub indices: Vec<u32>
+}
+
+impl Triangulation {
+    pub fn triangles(&self) -> impl Iterator<Item=[Vec2; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|t| [
+            self.vertices[t[0] as usize], self.vertices[t[1] as usize], self.vertices[t[2] as usize]
+        ])
+    }
+}
+
+/// ear clipping of every outer ring of the polygon together with its holes,
+/// so the output of `clip_polygon` can be passed in directly
+pub fn triangulate (polygon: &[Edge]) -> Triangulation {
+    let mut res = Triangulation::default();
+    for group in group_rings(polygon) {
+        let offset = res.vertices.len() as u32;
+        let part = triangulate_group(&group);
+        res.vertices.extend(part.vertices);
+        res.indices.extend(part.indices.iter().map(|i| i + offset));
+    }
+    res
+}
+
+/// ear clipping of an outer ring with holes
+pub fn triangulate_group (group: &RingGroup) -> Triangulation {
+    let vertices: Vec<Vec2> = group.outer.iter()
+        .chain(group.holes.iter().flatten())
+        .map(|e| e.from)
+        .collect();
+    let points: Vec<Point> = vertices.iter().map(|v| Vector2::new(v.x as f64, v.y as f64)).collect();
+    let mut ring = bridge_holes(group, &points);
+    let mut indices = vec![];
+    // in here rings are clockwise with y downwards, so convex corners turn with a positive cross product
+    let corner = |ring: &[usize], k: usize| {
+        let n = ring.len();
+        (ring[(k + n - 1) % n], ring[k], ring[(k + 1) % n])
+    };
+    let turn = |(a, b, c): (usize, usize, usize)| cross_product(&(points[b] - points[a]), &(points[c] - points[b]));
+    let mut k = 0;
+    let mut misses = 0;
+    while ring.len() >= 3 {
+        k %= ring.len();
+        let (a, b, c) = corner(&ring, k);
+        if turn((a, b, c)) == 0.0 && ring.len() > 3 {
+            // vertex on a straight line, it would give an empty triangle
+            ring.remove(k);
+            misses = 0;
+            continue
+        }
+        let is_ear = turn((a, b, c)) > 0.0 && (0..ring.len()).all(|j| {
+            let p = points[ring[j]];
+            // only reflex vertices can lie inside an ear
+            p == points[a] || p == points[b] || p == points[c] || turn(corner(&ring, j)) > 0.0
+                || !is_in_triangle(p, points[a], points[b], points[c])
+        });
+        // a ring without ears is degenerate. cut somewhere rather than looping forever
+        if is_ear || misses > ring.len() || ring.len() == 3 {
+            if turn((a, b, c)) > 0.0 {
+                indices.extend([c as u32, b as u32, a as u32]);
+            }
+            ring.remove(k);
+            misses = 0;
+        } else {
+            k += 1;
+            misses += 1;
+        }
+    }
+    Triangulation { vertices, indices }
+}
+
+type Point = Vector2<f64>;
+
+/// indices of the ring's vertices, reordered to be clockwise (positive area with y downwards)
+/// or counter-clockwise
+fn ring_indices(ring: &[Edge], first: usize, clockwise: bool) -> Vec<usize> {
+    let mut indices: Vec<usize> = (first..(first + ring.len())).collect();
+    if (signed_area(ring) > 0.0) != clockwise {
+        indices.reverse();
+    }
+    indices
+}
+
+fn is_in_triangle(p: Point, a: Point, b: Point, c: Point) -> bool {
+    cross_product(&(b - a), &(p - a)) >= 0.0
+        && cross_product(&(c - b), &(p - b)) >= 0.0
+        && cross_product(&(a - c), &(p - c)) >= 0.0
+}
+
+/// join the holes to the outer ring with bridges, so that a single ring remains.
+/// the vertices at both ends of a bridge appear twice in the ring
+fn bridge_holes(group: &RingGroup, points: &[Point]) -> Vec<usize> {
+    let mut ring = ring_indices(&group.outer, 0, true);
+    let mut first = group.outer.len();
+    let mut holes = vec![];
+    for hole in group.holes.iter() {
+        holes.push(ring_indices(hole, first, false));
+        first += hole.len();
+    }
+    let max_x = |h: &Vec<usize>| h.iter().map(|i| points[*i].x).fold(f64::MIN, f64::max);
+    holes.sort_by(|h1, h2| max_x(h2).total_cmp(&max_x(h1)));
+    for hole in holes {
+        let m_index = (0..hole.len()).max_by(|i, j| points[hole[*i]].x.total_cmp(&points[hole[*j]].x)).unwrap();
+        let m = points[hole[m_index]];
+        // cast a ray from m to the right and find the closest edge it hits
+        let mut hit: Option<(f64, usize)> = None;
+        for i in 0..ring.len() {
+            let (a, b) = (points[ring[i]], points[ring[(i + 1) % ring.len()]]);
+            if (a.y > m.y) == (b.y > m.y) { continue }
+            let x = a.x + (m.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if x >= m.x && hit.is_none_or(|(hx, _)| x < hx) {
+                hit = Some((x, i));
+            }
+        }
+        let Some((hit_x, edge)) = hit else { continue };
+        let hit_point = Vector2::new(hit_x, m.y);
+        let next = (edge + 1) % ring.len();
+        let (p_edge, p_next) = (points[ring[edge]], points[ring[next]]);
+        let mut bridge = if p_next == hit_point || (p_edge != hit_point && p_next.x > p_edge.x) {
+            next
+        } else {
+            edge
+        };
+        // a reflex vertex inside the triangle m, hit point, bridge end would block the bridge.
+        // take the one closest in angle to the ray instead
+        let p = points[ring[bridge]];
+        let (t0, t1, t2) = if cross_product(&(hit_point - m), &(p - m)) >= 0.0 { (m, hit_point, p) } else { (m, p, hit_point) };
+        let mut best_angle = f64::MAX;
+        for i in 0..ring.len() {
+            let prev = points[ring[(i + ring.len() - 1) % ring.len()]];
+            let (cur, next) = (points[ring[i]], points[ring[(i + 1) % ring.len()]]);
+            if i == bridge || cur.x < m.x || cross_product(&(cur - prev), &(next - cur)) > 0.0 {
+                continue
+            }
+            if is_in_triangle(cur, t0, t1, t2) {
+                let v = cur - m;
+                let angle = v.y.atan2(v.x).abs();
+                if angle < best_angle {
+                    best_angle = angle;
+                    bridge = i;
+                }
+            }
+        }
+        let mut merged = ring[..=bridge].to_vec();
+        merged.extend(hole[m_index..].iter().chain(hole[..=m_index].iter()));
+        merged.extend(ring[bridge..].iter());
+        ring = merged;
+    }
+    ring
+}
+
+#[cfg(test)]
+mod tests {
+    use speedy2d::dimen::Vec2;
+
+    use crate::edge::Edge;
+    use crate::fixtures::ring;
+    use crate::polygon::{is_point_in_polygon, point_vec_to_edges, signed_area};
+    use crate::triangulation::triangulate;
+
+    /// checks the winding of every triangle and returns the total area
+    fn triangle_area(polygon: &[Edge]) -> f32 {
+        triangulate(polygon).triangles().map(|t| {
+            let area = signed_area(&point_vec_to_edges(&t));
+            assert!(area < 0.0, "triangles should be counter-clockwise");
+            -area
+        }).sum()
+    }
+
+    #[test]
+    fn test_simple() {
+        let square = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)]);
+        assert_eq!(triangulate(&square).indices.len(), 6);
+        assert_eq!(triangle_area(&square), 1.0);
+        let l_shape = ring(&[(0.0, 0.0), (0.0, 2.0), (1.0, 2.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(triangulate(&l_shape).indices.len(), 12);
+        assert_eq!(triangle_area(&l_shape), 3.0);
+        let with_collinear = ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)]);
+        assert_eq!(triangle_area(&with_collinear), 2.0);
+    }
+
+    #[test]
+    fn test_holes() {
+        let mut polygon = ring(&[(0.0, 0.0), (0.0, 6.0), (6.0, 6.0), (6.0, 0.0)]);
+        polygon.extend(ring(&[(1.0, 1.0), (3.0, 1.0), (3.0, 3.0), (1.0, 3.0)]));
+        polygon.extend(ring(&[(4.0, 4.0), (5.0, 4.0), (5.0, 5.0), (4.0, 5.0)]));
+        polygon.extend(ring(&[(10.0, 0.0), (10.0, 1.0), (11.0, 1.0), (11.0, 0.0)]));
+        let res = triangulate(&polygon);
+        assert_eq!(res.vertices.len(), 16);
+        assert_eq!(res.indices.len(), 3 * (12 + 2 * 2 - 2 + 2));
+        assert_eq!(triangle_area(&polygon), 36.0 - 4.0 - 1.0 + 1.0);
+        for t in res.triangles() {
+            let t = point_vec_to_edges(&t);
+            assert!(!is_point_in_polygon(Vec2 { x: 2.0, y: 2.0 }, &t));
+            assert!(!is_point_in_polygon(Vec2 { x: 4.5, y: 4.5 }, &t));
+        }
+    }
+}
@@ -1,33 +1,155 @@
+use std::path::{Path, PathBuf};
+
 use speedy2d::{Graphics2D, Window};
 use speedy2d::color::Color;
-use speedy2d::dimen::Vec2;
-use speedy2d::font::{Font, TextLayout, TextOptions};
-use speedy2d::window::{KeyScancode, MouseButton, VirtualKeyCode, WindowHandler, WindowHelper};
+use speedy2d::dimen::{UVec2, Vec2};
+use speedy2d::font::{Font, TextAlignment, TextLayout, TextOptions};
+use speedy2d::shape::Rectangle;
+use speedy2d::window::{KeyScancode, ModifiersState, MouseButton, MouseScrollDistance, VirtualKeyCode, WindowHandler, WindowHelper};
 
-use crate::clipping::clip_polygon;
+use crate::boolean::{boolean_op, BooleanOp, FillRule};
+use crate::clipping::{clip_polygon_traced, TraceEvent};
 use crate::edge::Edge;
-use crate::polygon::{is_point_in_polygon, is_polygon_clockwise};
+use crate::generator::{random_points, random_space_partitioning, random_star, random_two_opt, random_with_holes, Rng};
+use crate::io::scene::{parse_scene, to_scene_json, Scene};
+use crate::polygon::{add_ring, is_point_in_polygon, point_vec_to_edges, signed_area, split_rings, Rings};
+use crate::render::{result_fill_color, scanline_spans, Camera, Span, CLIPPING_COLOR, EDGE_THICKNESS, GRID_COLOR, RESULT_COLOR, SUBJECT_COLOR};
 use crate::ui::UiState::WaitSubject;
 
-const EDGE_THICKNESS: f32 = 3.0;
 const CANDIDATE_THICKNESS: f32 = 1.0;
 const WIDTH: u32 = 1600;
 const HEIGHT: u32 = 900;
+/// how far the mouse moves with the button down before a click becomes a drag
+const DRAG_THRESHOLD: f32 = 4.0;
+/// zoom factor for one line of the mouse wheel
+const ZOOM_STEP: f32 = 1.2;
+/// how close to a vertex or an edge, in pixels, the cursor picks it
+const PICK_RADIUS: f32 = 8.0;
+/// intersections where the subject goes into the clipping polygon, and where it comes out
+const ENTERING_COLOR: Color = Color::MAGENTA;
+const EXITING_COLOR: Color = Color::DARK_GRAY;
+/// how many snapping steps there are between grid lines, chosen with [ and ]
+const SNAP_DIVISIONS: [u32; 5] = [1, 2, 4, 5, 10];
+const INPUT_HINTS: &str = "(Shift+RightMB=Close as Another Polygon, G=Grid Snap, [/]=Finer/Coarser Snap, Alt=No Snap, C=Type Coordinates)";
+/// in pixels, room for two lines of text
+const STATUS_BAR_HEIGHT: f32 = 56.0;
+const VIEW_HINTS: &str = "(Ctrl+Z=Undo, Ctrl+Shift+Z=Redo, Wheel=Zoom, Drag=Pan, F=Fit, Ctrl+S=Save, Ctrl+O=Load, R=Random Scene)\n\
+                          (Drag Vertex=Move, Shift+Click Edge=Insert Vertex, Del=Delete Vertex)";
 
-#[derive(PartialEq)]
+#[derive(Copy, Clone, PartialEq)]
 enum UiState {
     WaitSubject,
     WaitClipping,
     InputDone
 }
 
+#[derive(Copy, Clone, PartialEq)]
+enum Role {
+    Subject,
+    Clipping
+}
+
+/// the start of an edge of one of the subject or clipping polygons
+#[derive(Copy, Clone, PartialEq)]
+struct Vertex {
+    role: Role,
+    polygon: usize,
+    edge: usize
+}
+
+/// an operation on keys 1 to 4, with the colour its result is filled with and the symbol it is labelled with
+struct OpInfo {
+    op: BooleanOp,
+    name: &'static str,
+    color: &'static str,
+    symbol: &'static str
+}
+
+const OPS: [OpInfo; 4] = [
+    OpInfo { op: BooleanOp::Intersection, name: "Intersection", color: "CYAN", symbol: "&" },
+    OpInfo { op: BooleanOp::Union, name: "Union", color: "YELLOW", symbol: "|" },
+    OpInfo { op: BooleanOp::Difference, name: "Difference", color: "MAGENTA", symbol: "-" },
+    OpInfo { op: BooleanOp::Xor, name: "XOR", color: "ORANGE", symbol: "^" }
+];
+
+fn op_info(op: BooleanOp) -> &'static OpInfo {
+    OPS.iter().find(|info| info.op == op).unwrap()
+}
+
+/// the clipper's steps, of which the first `shown` are drawn, and the polygons it clipped,
+/// whose edges the steps refer to
+struct StepView {
+    trace: Vec<TraceEvent>,
+    shown: usize,
+    subject: Vec<Edge>,
+    clipping: Vec<Edge>
+}
+
+/// everything the user has drawn, to restore at once
+#[derive(Clone)]
+struct Drawing {
+    state: UiState,
+    subject: Vec<Rings>,
+    clipping: Vec<Rings>,
+    new_polygon_part: Vec<Vec2>
+}
+
+/// a change that can be undone and done again
+enum Command {
+    AddVertex(Vec2),
+    /// the ring being drawn goes into the polygon it nests with, which `add_ring` may turn rings of,
+    /// or else starts another polygon. a ring it refuses is never recorded
+    CloseRing { role: Role, points: Vec<Vec2>, before: Vec<Rings>, after: Vec<Rings> },
+    CancelRing(Vec<Vec2>),
+    MoveVertex { vertex: Vertex, from: Vec2, to: Vec2 },
+    InsertVertex { vertex: Vertex, at: Vec2 },
+    /// removing a vertex of a triangle deletes the ring, and the polygon with its last ring
+    RemoveVertex { vertex: Vertex, before: Vec<Rings> },
+    SetState { from: UiState, to: UiState },
+    /// clearing everything, or loading a scene
+    Replace { before: Drawing, after: Drawing }
+}
+
 pub struct UiLogic {
     state: UiState,
-    subject_polygon: Vec<Edge>,
-    clipping_polygon: Vec<Edge>,
+    /// independent polygons, which may overlap. rings are only nested within one polygon
+    subject_polygons: Vec<Rings>,
+    clipping_polygons: Vec<Rings>,
     result_polygon: Vec<Edge>,
+    /// the result of each subject polygon with each clipping polygon, labelled like "S1 & C2"
+    results: Vec<(String, Vec<Edge>)>,
+    /// intersection runs `clip_polygon`, the others `boolean_op`
+    op: BooleanOp,
     new_polygon_part: Vec<Vec2>,
+    /// in pixels, like everything the window reports. polygons are in world coordinates
     cursor: Vec2,
+    camera: Camera,
+    size: UVec2,
+    /// where the left button went down, while it is down
+    pressed_at: Option<Vec2>,
+    /// whether the mouse moved far enough since then to drag
+    dragging: bool,
+    /// the vertex the left button went down on and where it was, which a drag moves. otherwise a drag pans
+    grabbed: Option<(Vertex, Vec2)>,
+    selected: Option<Vertex>,
+    grid_snap: bool,
+    /// index into `SNAP_DIVISIONS`
+    snap_division: usize,
+    /// the coordinates being typed for the next vertex
+    coordinate_prompt: Option<String>,
+    undo_stack: Vec<Command>,
+    redo_stack: Vec<Command>,
+    /// stepping through the clip instead of showing its result
+    steps: Option<StepView>,
+    /// the rows of the result's fill on screen, and the view they were worked out for
+    result_fill: Option<(Camera, UVec2, Vec<Span>)>,
+    modifiers: ModifiersState,
+    /// where Ctrl+S saves the scene and Ctrl+O loads it from
+    scene_path: Option<PathBuf>,
+    /// the seed of the next random scene
+    random_seed: u64,
+    /// the outcome of the last save or load
+    message: Option<String>,
     font: Font
 }
 
@@ -36,74 +158,173 @@ impl Default for UiLogic {
         let font_bytes = include_bytes!("../assets/LiberationSans-Regular.ttf");
         UiLogic {
             state: WaitSubject,
-            subject_polygon: vec![],
-            clipping_polygon: vec![],
+            subject_polygons: vec![],
+            clipping_polygons: vec![],
             result_polygon: vec![],
+            results: vec![],
+            op: BooleanOp::Intersection,
             new_polygon_part: vec![],
             cursor: Vec2 { x: 0.0, y: 0.0 },
+            camera: Camera::default(),
+            size: UVec2::new(WIDTH, HEIGHT),
+            pressed_at: None,
+            dragging: false,
+            grabbed: None,
+            selected: None,
+            grid_snap: false,
+            snap_division: 0,
+            coordinate_prompt: None,
+            undo_stack: vec![],
+            redo_stack: vec![],
+            steps: None,
+            result_fill: None,
+            modifiers: ModifiersState::default(),
+            scene_path: None,
+            random_seed: 0,
+            message: None,
             font: Font::new(font_bytes).expect("Font loading failed")
         }
     }
 }
 
-fn draw_polygon_with_hint(points: &Vec<Vec2>, cursor: &Vec2, color: Color, graphics: &mut Graphics2D) {
+fn draw_polygon_with_hint(points: &[Vec2], cursor: &Vec2, camera: Camera, color: Color, graphics: &mut Graphics2D) {
     for (i, point) in points.iter().enumerate() {
         if i == points.len() - 1 { break; }
-        graphics.draw_line(point, points[i + 1], EDGE_THICKNESS, color)
+        graphics.draw_line(camera.to_screen(*point), camera.to_screen(points[i + 1]), EDGE_THICKNESS, color)
     }
     if !points.is_empty() {
-        graphics.draw_line(points.last().unwrap(), cursor, CANDIDATE_THICKNESS, color)
+        graphics.draw_line(camera.to_screen(*points.last().unwrap()), cursor, CANDIDATE_THICKNESS, color)
     }
 }
 
-fn draw_polygon(edges: &Vec<Edge>, thickness: f32, color: Color, graphics: &mut Graphics2D) {
+fn draw_polygon(edges: &[Edge], camera: Camera, thickness: f32, color: Color, graphics: &mut Graphics2D) {
     if edges.is_empty() { return }
     for edge in edges.iter() {
-        graphics.draw_line(edge.from, edge.to, thickness, color)
+        graphics.draw_line(camera.to_screen(edge.from), camera.to_screen(edge.to), thickness, color)
+    }
+}
+
+/// wrapped to the window, returning where the text ends
+fn draw_state_text(text: &str, size: UVec2, font: &Font, graphics: &mut Graphics2D) -> f32 {
+    let options = TextOptions::new().with_wrap_to_width(size.x as f32 - 100.0, TextAlignment::Left);
+    let blk = font.layout_text(&format!("{}\n{}", text, VIEW_HINTS), 32.0, options);
+    graphics.draw_text((50.0, 50.0), Color::BLACK, &blk);
+    50.0 + blk.height()
+}
+
+/// the walked parts of the result and the added loops so far, and the intersections found so far
+/// labelled with their ids. the latest step is drawn in black, or in gray for a loop that is not added
+fn draw_steps(steps: &[TraceEvent], subject: &[Edge], clipping: &[Edge], camera: Camera, font: &Font, graphics: &mut Graphics2D) {
+    let intersections: Vec<(usize, Vec2, bool)> = steps.iter().filter_map(|event| match event {
+        TraceEvent::Intersection { id, point, entering, .. } => Some((*id, *point, *entering)),
+        _ => None
+    }).collect();
+    let point_of = |id: usize| intersections.iter().find(|x| x.0 == id).map(|x| x.1);
+    for (i, event) in steps.iter().enumerate() {
+        let latest = i == steps.len() - 1;
+        match event {
+            TraceEvent::Walk { from, to, .. } => {
+                let (thickness, color) = if latest { (EDGE_THICKNESS * 2.0, Color::BLACK) } else { (EDGE_THICKNESS, RESULT_COLOR) };
+                graphics.draw_line(camera.to_screen(*from), camera.to_screen(*to), thickness, color)
+            }
+            TraceEvent::Loop { on_subject, edges, added } if *added || latest => {
+                let polygon = if *on_subject { subject } else { clipping };
+                let color = if !latest { RESULT_COLOR } else if *added { Color::BLACK } else { Color::GRAY };
+                let thickness = if latest { EDGE_THICKNESS * 2.0 } else { EDGE_THICKNESS };
+                for e in edges.iter().map(|i| polygon[*i]) {
+                    graphics.draw_line(camera.to_screen(e.from), camera.to_screen(e.to), thickness, color)
+                }
+            }
+            TraceEvent::Start { id, .. } | TraceEvent::Switch { id, .. } | TraceEvent::Close { id } if latest => {
+                if let Some(point) = point_of(*id) {
+                    graphics.draw_circle(camera.to_screen(point), PICK_RADIUS * 1.5, Color::BLACK)
+                }
+            }
+            _ => {}
+        }
+    }
+    for (id, point, entering) in intersections.iter() {
+        let latest = matches!(steps.last(), Some(TraceEvent::Intersection { id: last, .. }) if last == id);
+        let p = camera.to_screen(*point);
+        if latest {
+            graphics.draw_circle(p, PICK_RADIUS * 1.5, Color::BLACK)
+        }
+        graphics.draw_circle(p, PICK_RADIUS, if *entering { ENTERING_COLOR } else { EXITING_COLOR });
+        let label = format!("{} {}", id, if *entering { "in" } else { "out" });
+        let blk = font.layout_text(&label, 20.0, TextOptions::new());
+        graphics.draw_text(p + Vec2::new(PICK_RADIUS, PICK_RADIUS), Color::BLACK, &blk)
     }
 }
 
-fn point_vec_to_edges(points: &Vec<Vec2>) -> Vec<Edge> {
-    if points.len() < 3 {
-        panic!("Malformed polygon!")
+/// below the state text, which ends at `top`
+fn draw_message(text: &str, top: f32, size: UVec2, font: &Font, graphics: &mut Graphics2D) {
+    let options = TextOptions::new().with_wrap_to_width(size.x as f32 - 100.0, TextAlignment::Left);
+    let blk = font.layout_text(text, 24.0, options);
+    graphics.draw_text((50.0, top + 10.0), Color::BLACK, &blk)
+}
+
+/// text just above the topmost vertex
+fn draw_label(text: &str, polygon: &[Edge], camera: Camera, color: Color, font: &Font, graphics: &mut Graphics2D) {
+    let top = polygon.iter().map(|e| camera.to_screen(e.from)).min_by(|a, b| a.y.total_cmp(&b.y));
+    if let Some(top) = top {
+        let blk = font.layout_text(text, 20.0, TextOptions::new());
+        graphics.draw_text((top.x, top.y - 24.0), color, &blk)
     }
-    let mut ret: Vec<Edge> = vec![];
-    for (i, point) in points.iter().enumerate() {
-        if i == points.len() - 1 { break; }
-        ret.push(Edge { from: *point, to: points[i + 1] })
+}
+
+/// a strip along the bottom of the window
+fn draw_status_bar(text: &str, size: UVec2, font: &Font, graphics: &mut Graphics2D) {
+    let top = size.y as f32 - STATUS_BAR_HEIGHT;
+    graphics.draw_rectangle(Rectangle::new(Vec2::new(0.0, top), Vec2::new(size.x as f32, size.y as f32)),
+                            Color::from_rgb(0.93, 0.93, 0.93));
+    let blk = font.layout_text(text, 20.0, TextOptions::new());
+    graphics.draw_text((10.0, top + 6.0), Color::BLACK, &blk)
+}
+
+/// e.g. "subject" for the status bar
+fn role_name(role: Role) -> &'static str {
+    match role {
+        Role::Subject => "subject",
+        Role::Clipping => "clipping"
     }
-    ret.push(Edge { from: *points.last().unwrap(), to: points[0] });
-    ret
 }
 
-fn draw_state_text(text: &str, font: &Font, graphics: &mut Graphics2D) {
-    let blk = font.layout_text(text, 32.0, TextOptions::new());
-    graphics.draw_text((50.0, 50.0), Color::BLACK, &blk)
+/// area, vertex and ring count. outer rings are counter-clockwise and have negative signed area
+fn polygon_summary(name: &str, polygon: &[Edge]) -> String {
+    format!("{}: area {:.2}, {} vertices, {} rings", name, -signed_area(polygon), polygon.len(), split_rings(polygon).len())
 }
 
-fn draw_grid(graphics: &mut Graphics2D) {
-    for i in 1..100 {
-        let j = i as f32;
-        graphics.draw_line(&Vec2{x: j * 100.0, y: 0.0},
-                           &Vec2 {x: j * 100.0, y: 5000.0},
-                           1.0, Color::LIGHT_GRAY);
-        graphics.draw_line(&Vec2{y: j * 100.0, x: 0.0},
-                           &Vec2 {y: j * 100.0, x: 5000.0},
-                           1.0, Color::LIGHT_GRAY);
+/// a power of ten, so that lines are at least 50 pixels apart
+fn grid_spacing(scale: f32) -> f32 {
+    10f32.powf((50.0 / scale).log10().ceil())
+}
+
+/// two numbers, separated by a comma or spaces
+fn parse_coordinates(text: &str) -> Option<Vec2> {
+    let numbers: Vec<f32> = text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<f32>().ok().filter(|x| x.is_finite()))
+        .collect::<Option<_>>()?;
+    match numbers.as_slice() {
+        [x, y] => Some(Vec2::new(*x, *y)),
+        _ => None
     }
 }
 
-fn draw_result_overlay(result: &[Edge], subject: &[Edge], clipping: &[Edge], graphics: &mut Graphics2D) {
-    for i in (1..WIDTH).step_by(5) {
-        for j in (1..HEIGHT).step_by(5) {
-            let point = Vec2 { x: (i as f32), y: (j as f32) };
-            if ! is_point_in_polygon(point, result)
-                || ! is_point_in_polygon(point, subject)
-                || ! is_point_in_polygon(point, clipping) {
-                continue;
-            }
-            graphics.draw_circle(point, 2.0, Color::CYAN);
-        }
+fn draw_grid(camera: Camera, size: UVec2, graphics: &mut Graphics2D) {
+    let (xs, ys) = camera.grid_lines(grid_spacing(camera.scale), size.x, size.y);
+    for x in xs {
+        graphics.draw_line(Vec2 { x, y: 0.0 }, Vec2 { x, y: size.y as f32 }, 1.0, GRID_COLOR);
+    }
+    for y in ys {
+        graphics.draw_line(Vec2 { x: 0.0, y }, Vec2 { x: size.x as f32, y }, 1.0, GRID_COLOR);
+    }
+}
+
+fn draw_spans(spans: &[Span], color: Color, graphics: &mut Graphics2D) {
+    for span in spans {
+        let (x, y) = (span.x.start as f32, span.y as f32);
+        graphics.draw_rectangle(Rectangle::from_tuples((x, y), (span.x.end as f32, y + 1.0)), color)
     }
 }
 
@@ -111,61 +332,173 @@ impl WindowHandler for UiLogic {
     fn on_draw(&mut self, helper: &mut WindowHelper<()>, graphics: &mut Graphics2D) {
         graphics.clear_screen(Color::WHITE);
 
-        draw_grid(graphics);
-        match self.state {
+        draw_grid(self.camera, self.size, graphics);
+        let (snapped, is_snapped) = self.snap(self.cursor, self.grabbed.map(|(vertex, _)| vertex));
+        let snapped_on_screen = self.camera.to_screen(snapped);
+        let snap_status = if self.grid_snap { format!(", Grid Snap {}", self.snap_step()) } else { String::new() };
+        let text_bottom = match self.state {
             WaitSubject => {
-                draw_state_text("Input Subject Polygon (RED)\n(Esc=Undo, RightMB=Close Curve, Enter=Next Step)", &self.font, graphics);
-                draw_polygon_with_hint(&self.new_polygon_part, &self.cursor, Color::RED, graphics);
+                draw_polygon_with_hint(&self.new_polygon_part, &snapped_on_screen, self.camera, SUBJECT_COLOR, graphics);
+                draw_state_text(&format!("Input Subject Polygons (RED){}\n(Esc=Cancel, RightMB=Close Curve, Enter=Next Step)\n{}",
+                                         snap_status, INPUT_HINTS), self.size, &self.font, graphics)
             },
             UiState::WaitClipping => {
-                draw_state_text("Input Clipping Polygon (GREEN)\n(Esc=Undo, RightMB=Close Curve, Enter=Next Step)", &self.font, graphics);
-                draw_polygon_with_hint(&self.new_polygon_part, &self.cursor, Color::GREEN, graphics);
+                draw_polygon_with_hint(&self.new_polygon_part, &snapped_on_screen, self.camera, CLIPPING_COLOR, graphics);
+                draw_state_text(&format!("Input Clipping Polygons (GREEN){}\n(Esc=Cancel, RightMB=Close Curve, Enter=Next Step)\n{}",
+                                         snap_status, INPUT_HINTS), self.size, &self.font, graphics)
             },
-            UiState::InputDone => {
-                draw_state_text("Result (BLUE)\n(Enter = Clear)", &self.font, graphics);
+            UiState::InputDone => match &self.steps {
+                None => {
+                    let info = op_info(self.op);
+                    let text = format!("{} ({})\n(Enter = Clear, 1-4=Intersection/Union/Difference/XOR, T=Step Through Clip)", info.name, info.color);
+                    draw_state_text(&text, self.size, &self.font, graphics)
+                },
+                Some(steps) => {
+                    let step = match steps.shown {
+                        0 => "Start".to_string(),
+                        n => format!("Step {}/{}: {}", n, steps.trace.len(), steps.trace[n - 1])
+                    };
+                    draw_state_text(&format!("{}\n(Left/Right=Previous/Next Step, T=Show Result)", step), self.size, &self.font, graphics)
+                }
+            }
+        };
+        if let Some(text) = &self.coordinate_prompt {
+            draw_message(&format!("Next vertex (x, y): {}_  (Enter=Add, Esc=Cancel)", text), text_bottom, self.size, &self.font, graphics);
+        } else if let Some(message) = &self.message {
+            draw_message(message, text_bottom, self.size, &self.font, graphics);
+        }
+        if is_snapped && (self.state != UiState::InputDone || self.grabbed.is_some()) {
+            let half = Vec2::new(PICK_RADIUS, PICK_RADIUS) / 2.0;
+            graphics.draw_rectangle(Rectangle::new(snapped_on_screen - half, snapped_on_screen + half), Color::DARK_GRAY);
+        }
+        for (polygons, color) in [(&self.subject_polygons, SUBJECT_COLOR), (&self.clipping_polygons, CLIPPING_COLOR)] {
+            for polygon in polygons.iter() {
+                draw_polygon(&polygon.edges, self.camera, EDGE_THICKNESS, color, graphics);
+            }
+        }
+        if let Some(steps) = &self.steps {
+            draw_steps(&steps.trace[..steps.shown], &steps.subject, &steps.clipping, self.camera, &self.font, graphics);
+        } else {
+            let view = (self.camera, self.size);
+            if !matches!(&self.result_fill, Some((camera, size, _)) if (*camera, *size) == view) {
+                let on_screen: Vec<Edge> = self.result_polygon.iter()
+                    .map(|e| Edge { from: self.camera.to_screen(e.from), to: self.camera.to_screen(e.to) })
+                    .collect();
+                let spans = scanline_spans(&on_screen, FillRule::NonZero, self.size.x, self.size.y);
+                self.result_fill = Some((self.camera, self.size, spans));
+            }
+            draw_spans(&self.result_fill.as_ref().unwrap().2, result_fill_color(self.op), graphics);
+            draw_polygon(&self.result_polygon, self.camera, EDGE_THICKNESS, RESULT_COLOR, graphics);
+            for (label, result) in self.results.iter() {
+                draw_label(label, result, self.camera, RESULT_COLOR, &self.font, graphics);
             }
         }
-        draw_polygon(&self.subject_polygon, EDGE_THICKNESS, Color::RED, graphics);
-        draw_polygon(&self.clipping_polygon, EDGE_THICKNESS,Color::GREEN, graphics);
-        if self.result_polygon.len() >= 3 {
-            draw_result_overlay(&self.result_polygon, &self.subject_polygon, &self.clipping_polygon, graphics);
+        for (name, polygons, color) in [("S", &self.subject_polygons, SUBJECT_COLOR), ("C", &self.clipping_polygons, CLIPPING_COLOR)] {
+            for (i, polygon) in polygons.iter().enumerate() {
+                draw_label(&format!("{}{}", name, i + 1), &polygon.edges, self.camera, color, &self.font, graphics);
+            }
+        }
+        if let Some(vertex) = self.vertex_at(self.cursor).filter(|_| self.new_polygon_part.is_empty()) {
+            graphics.draw_circle(self.camera.to_screen(self.vertex_position(vertex)), PICK_RADIUS, Color::GRAY);
+        }
+        if let Some(vertex) = self.selected {
+            graphics.draw_circle(self.camera.to_screen(self.vertex_position(vertex)), PICK_RADIUS / 2.0, Color::BLACK);
         }
-        draw_polygon(&self.result_polygon, EDGE_THICKNESS, Color::BLUE, graphics);
+        draw_status_bar(&self.status_text(), self.size, &self.font, graphics);
 
         helper.request_redraw()
     }
 
+    fn on_resize(&mut self, _helper: &mut WindowHelper<()>, size_pixels: UVec2) {
+        self.size = size_pixels;
+    }
+
     fn on_mouse_move(&mut self, _helper: &mut WindowHelper<()>, position: Vec2) {
+        if let Some(start) = self.pressed_at {
+            self.dragging |= (position - start).magnitude() > DRAG_THRESHOLD;
+        }
+        if self.dragging {
+            match self.grabbed {
+                Some((vertex, _)) => {
+                    let to = self.snap(position, Some(vertex)).0;
+                    self.polygon_mut(vertex).move_vertex(vertex.edge, to);
+                    self.update_result();
+                }
+                None => self.camera.pan(position - self.cursor)
+            }
+        }
         self.cursor = position;
     }
 
+    fn on_mouse_wheel_scroll(&mut self, _helper: &mut WindowHelper<()>, distance: MouseScrollDistance) {
+        let lines = match distance {
+            MouseScrollDistance::Lines { y, .. } => y as f32,
+            MouseScrollDistance::Pixels { y, .. } => y as f32 / 40.0,
+            MouseScrollDistance::Pages { y, .. } => y as f32 * 10.0
+        };
+        self.camera.zoom_at(self.cursor, ZOOM_STEP.powf(lines));
+    }
+
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper<()>, button: MouseButton) {
+        if button != MouseButton::Left { return }
+        if self.new_polygon_part.is_empty() {
+            if let Some(vertex) = self.vertex_at(self.cursor) {
+                self.grabbed = Some((vertex, self.vertex_position(vertex)));
+            } else if self.modifiers.shift() {
+                if let Some((vertex, at)) = self.edge_at(self.cursor) {
+                    let vertex = Vertex { edge: vertex.edge + 1, ..vertex };
+                    self.execute(Command::InsertVertex { vertex, at });
+                    self.grabbed = Some((vertex, at));
+                }
+            }
+            self.selected = self.grabbed.map(|(vertex, _)| vertex);
+        }
+        // after the insert, which lets go of the mouse like any other change
+        self.pressed_at = Some(self.cursor);
+    }
+
     fn on_mouse_button_up(&mut self, _helper: &mut WindowHelper<()>, button: MouseButton) {
         match button {
             MouseButton::Left => {
-                if self.state != UiState::InputDone {
-                    if let Some(x) = self.new_polygon_part.last() {
-                        if (x - self.cursor).magnitude_squared() < 1.0 {
-                            return; // avoid malformed edges
-                        }
+                // a change made while the button was down let go of it
+                if self.pressed_at.take().is_none() { return }
+                let dragged = std::mem::take(&mut self.dragging);
+                if let Some((vertex, from)) = self.grabbed.take() {
+                    let to = self.vertex_position(vertex);
+                    if dragged && to != from {
+                        // the drag already moved it
+                        self.record(Command::MoveVertex { vertex, from, to });
                     }
-                    self.new_polygon_part.push(self.cursor)
+                    return;
+                }
+                if dragged || self.state == UiState::InputDone {
+                    return;
                 }
+                self.add_vertex(self.snap(self.cursor, None).0)
             }
             MouseButton::Right => {
                 if self.new_polygon_part.len() < 3 { return; }
-                let target = match self.state {
-                    WaitSubject => { &mut self.subject_polygon },
-                    UiState::WaitClipping => { &mut self.clipping_polygon }
+                let role = match self.state {
+                    WaitSubject => { Role::Subject },
+                    UiState::WaitClipping => { Role::Clipping }
                     _ => { return; }
                 };
-                let np = &self.new_polygon_part;
-                let poly: Vec<Edge> = np.iter().zip(
-                    np.iter().skip(1).chain(np.iter().take(1))
-                ).map(|(p1, p2)| Edge { from: *p1, to: *p2 }).collect();
-                if target.is_empty() ^ is_polygon_clockwise(&poly) {
-                    target.append(&mut point_vec_to_edges(&self.new_polygon_part));
+                let ring = point_vec_to_edges(&self.new_polygon_part);
+                let before = self.polygons(role).to_vec();
+                // the latest polygon the ring nests with, unless Shift keeps it apart
+                let nesting = before.iter().rposition(|polygon| polygon.nests(&ring)).filter(|_| !self.modifiers.shift());
+                let polygon = nesting.map_or_else(Rings::default, |i| before[i].clone());
+                match add_ring(&polygon, &ring) {
+                    Ok(polygon) => {
+                        let mut after = before.clone();
+                        match nesting {
+                            Some(i) => after[i] = polygon,
+                            None => after.push(polygon)
+                        }
+                        self.execute(Command::CloseRing { role, points: self.new_polygon_part.clone(), before, after })
+                    }
+                    Err(error) => self.message = Some(format!("Cannot close the ring: {}", error))
                 }
-                self.new_polygon_part.clear();
             }
             MouseButton::Middle => {
                 self.cancel_current_polygon();
@@ -174,27 +507,106 @@ impl WindowHandler for UiLogic {
         }
     }
 
+    fn on_keyboard_modifiers_changed(&mut self, _helper: &mut WindowHelper<()>, state: ModifiersState) {
+        self.modifiers = state;
+    }
+
+    fn on_keyboard_char(&mut self, _helper: &mut WindowHelper<()>, unicode_codepoint: char) {
+        if let Some(text) = &mut self.coordinate_prompt {
+            if unicode_codepoint.is_ascii_digit() || ".,-+eE ".contains(unicode_codepoint) {
+                text.push(unicode_codepoint)
+            }
+        }
+    }
+
     fn on_key_down(&mut self, _helper: &mut WindowHelper<()>, virtual_key_code: Option<VirtualKeyCode>, _scancode: KeyScancode) {
+        if let Some(text) = &mut self.coordinate_prompt {
+            match virtual_key_code {
+                Some(VirtualKeyCode::Return) => {
+                    let text = self.coordinate_prompt.take().unwrap();
+                    match parse_coordinates(&text) {
+                        Some(point) => self.add_vertex(point),
+                        None => self.message = Some(format!("Not a point: {}", text))
+                    }
+                }
+                Some(VirtualKeyCode::Escape) => self.coordinate_prompt = None,
+                Some(VirtualKeyCode::Backspace) => { text.pop(); }
+                _ => {}
+            }
+            return
+        }
         match virtual_key_code {
             None => {}
             Some(keycode) => {
-                if keycode == VirtualKeyCode::Return {
+                if self.modifiers.ctrl() && keycode == VirtualKeyCode::Z {
+                    if self.modifiers.shift() { self.redo() } else { self.undo() }
+                } else if self.modifiers.ctrl() && keycode == VirtualKeyCode::Y {
+                    self.redo()
+                } else if self.modifiers.ctrl() && keycode == VirtualKeyCode::S {
+                    self.save_scene()
+                } else if self.modifiers.ctrl() && keycode == VirtualKeyCode::O {
+                    match self.scene_path.clone() {
+                        Some(path) => self.load_scene(&path),
+                        None => self.message = Some("No scene file to load, pass one on the command line".to_string())
+                    }
+                } else if keycode == VirtualKeyCode::Delete || keycode == VirtualKeyCode::Backspace {
+                    if let Some(vertex) = self.selected.take() {
+                        let before = self.polygons(vertex.role).to_vec();
+                        self.execute(Command::RemoveVertex { vertex, before });
+                    }
+                } else if keycode == VirtualKeyCode::T && self.state == UiState::InputDone {
+                    if self.op != BooleanOp::Intersection {
+                        self.message = Some("Only the intersection can be stepped through".to_string());
+                        return
+                    }
+                    if self.subject_polygons.len() != 1 || self.clipping_polygons.len() != 1 {
+                        self.message = Some("Only a single subject and clipping polygon can be stepped through".to_string());
+                        return
+                    }
+                    self.steps = match self.steps {
+                        None => Some(StepView { trace: vec![], shown: 0, subject: vec![], clipping: vec![] }),
+                        Some(_) => None
+                    };
+                    self.update_result()
+                } else if let Some(i) = [VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3, VirtualKeyCode::Key4]
+                    .iter().position(|k| *k == keycode) {
+                    self.op = OPS[i].op;
+                    self.steps = None;
+                    self.update_result()
+                } else if keycode == VirtualKeyCode::Right || keycode == VirtualKeyCode::Left {
+                    if let Some(steps) = &mut self.steps {
+                        steps.shown = if keycode == VirtualKeyCode::Right {
+                            (steps.shown + 1).min(steps.trace.len())
+                        } else {
+                            steps.shown.saturating_sub(1)
+                        }
+                    }
+                } else if keycode == VirtualKeyCode::C && self.state != UiState::InputDone {
+                    self.coordinate_prompt = Some(String::new())
+                } else if keycode == VirtualKeyCode::G {
+                    self.grid_snap = !self.grid_snap
+                } else if keycode == VirtualKeyCode::LBracket || keycode == VirtualKeyCode::RBracket {
+                    self.grid_snap = true;
+                    self.snap_division = if keycode == VirtualKeyCode::LBracket {
+                        (self.snap_division + 1).min(SNAP_DIVISIONS.len() - 1)
+                    } else {
+                        self.snap_division.saturating_sub(1)
+                    }
+                } else if keycode == VirtualKeyCode::F {
+                    self.fit_to_content()
+                } else if keycode == VirtualKeyCode::R && self.new_polygon_part.is_empty() {
+                    self.random_scene()
+                } else if keycode == VirtualKeyCode::Return {
                     if ! self.new_polygon_part.is_empty() { return }
                     match self.state {
                         WaitSubject => {
-                            if self.new_polygon_part.len() < 3 && !self.new_polygon_part.is_empty() { return; }
-                            self.state = UiState::WaitClipping
+                            self.execute(Command::SetState { from: WaitSubject, to: UiState::WaitClipping })
                         },
                         UiState::WaitClipping => {
-                            if self.new_polygon_part.len() < 3 && !self.new_polygon_part.is_empty() { return; }
-                            self.result_polygon = clip_polygon(&self.subject_polygon, &self.clipping_polygon);
-                            self.state = UiState::InputDone
+                            self.execute(Command::SetState { from: UiState::WaitClipping, to: UiState::InputDone })
                         },
                         UiState::InputDone => {
-                            self.clipping_polygon.clear();
-                            self.subject_polygon.clear();
-                            self.result_polygon.clear();
-                            self.state = WaitSubject
+                            self.replace(Drawing { state: WaitSubject, subject: vec![], clipping: vec![], new_polygon_part: vec![] })
                         }
                     }
                 } else if keycode == VirtualKeyCode::Escape {
@@ -206,19 +618,344 @@ impl WindowHandler for UiLogic {
 }
 
 impl UiLogic {
+    fn polygons(&self, role: Role) -> &[Rings] {
+        match role {
+            Role::Subject => &self.subject_polygons,
+            Role::Clipping => &self.clipping_polygons
+        }
+    }
+
+    fn polygons_mut(&mut self, role: Role) -> &mut Vec<Rings> {
+        match role {
+            Role::Subject => &mut self.subject_polygons,
+            Role::Clipping => &mut self.clipping_polygons
+        }
+    }
+
+    /// the polygon the vertex is in
+    fn polygon(&self, vertex: Vertex) -> &Rings {
+        &self.polygons(vertex.role)[vertex.polygon]
+    }
+
+    fn polygon_mut(&mut self, vertex: Vertex) -> &mut Rings {
+        &mut self.polygons_mut(vertex.role)[vertex.polygon]
+    }
+
+    /// every edge of every polygon, and the vertex it starts at
+    fn edges(&self) -> impl Iterator<Item = (Vertex, &Edge)> + '_ {
+        [Role::Subject, Role::Clipping].into_iter().flat_map(move |role| {
+            self.polygons(role).iter().enumerate().flat_map(move |(polygon, rings)| {
+                rings.edges.iter().enumerate().map(move |(edge, e)| (Vertex { role, polygon, edge }, e))
+            })
+        })
+    }
+
+    fn vertex_position(&self, vertex: Vertex) -> Vec2 {
+        self.polygon(vertex).edges[vertex.edge].from
+    }
+
+    /// the vertex nearest to a point on screen, if it is close enough to pick
+    fn vertex_at(&self, screen: Vec2) -> Option<Vertex> {
+        self.edges()
+            .map(|(v, e)| (v, (self.camera.to_screen(e.from) - screen).magnitude()))
+            .filter(|(_, distance)| *distance <= PICK_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(v, _)| v)
+    }
+
+    /// the edge nearest to a point on screen, and the nearest point of it in world coordinates
+    fn edge_at(&self, screen: Vec2) -> Option<(Vertex, Vec2)> {
+        let world = self.camera.to_world(screen);
+        self.edges()
+            .map(|(v, e)| (v, e.closest_point(world)))
+            .map(|(v, at)| (v, at, (self.camera.to_screen(at) - screen).magnitude()))
+            .filter(|(_, _, distance)| *distance <= PICK_RADIUS)
+            .min_by(|a, b| a.2.total_cmp(&b.2))
+            .map(|(v, at, _)| (v, at))
+    }
+
+    /// the world position under the cursor, what is nearest to it, which polygons it is in, and the
+    /// size of each polygon
+    fn status_text(&self) -> String {
+        let world = self.camera.to_world(self.cursor);
+        let describe = |vertex: Vertex, what: &str| {
+            let ring = self.polygon(vertex).ring_of(vertex.edge);
+            format!("{} {} {} {} (ring {})", role_name(vertex.role), vertex.polygon + 1, what, vertex.edge, ring)
+        };
+        let nearest = match (self.vertex_at(self.cursor), self.edge_at(self.cursor)) {
+            (Some(vertex), _) => describe(vertex, "vertex"),
+            (None, Some((vertex, _))) => describe(vertex, "edge"),
+            (None, None) => "nothing".to_string()
+        };
+        let is_inside = |polygon: &[Edge]| !polygon.is_empty() && is_point_in_polygon(world, polygon);
+        let inside = |polygons: &[Rings]| if polygons.iter().any(|p| is_inside(&p.edges)) { "yes" } else { "no" };
+        let all = |polygons: &[Rings]| polygons.iter().flat_map(|p| p.edges.iter().copied()).collect::<Vec<Edge>>();
+        format!("x: {:.3}, y: {:.3} | nearest: {} | inside subject: {}, clipping: {}, result: {}\n{} | {} | {}",
+                world.x, world.y, nearest,
+                inside(&self.subject_polygons), inside(&self.clipping_polygons),
+                if is_inside(&self.result_polygon) { "yes" } else { "no" },
+                polygon_summary("subject", &all(&self.subject_polygons)),
+                polygon_summary("clipping", &all(&self.clipping_polygons)),
+                polygon_summary("result", &self.result_polygon))
+    }
+
+    /// the distance between the points that grid snapping goes to
+    fn snap_step(&self) -> f32 {
+        grid_spacing(self.camera.scale) / SNAP_DIVISIONS[self.snap_division] as f32
+    }
+
+    /// where a point on screen goes in the world: onto a vertex or edge close enough to pick, or else
+    /// the grid if grid snapping is on. not onto the ring of the vertex being moved, which would
+    /// fold the ring onto itself. also whether it snapped. holding Alt turns snapping off
+    fn snap(&self, screen: Vec2, moving: Option<Vertex>) -> (Vec2, bool) {
+        let world = self.camera.to_world(screen);
+        if self.modifiers.alt() { return (world, false) }
+        let moving_ring = moving.map(|v| (v.role, v.polygon, self.polygon(v).ring_range(v.edge)));
+        let kept = |v: Vertex| !matches!(&moving_ring, Some((role, polygon, ring))
+            if (*role, *polygon) == (v.role, v.polygon) && ring.contains(&v.edge));
+        let distance = |p: Vec2| (self.camera.to_screen(p) - screen).magnitude();
+        let nearest = |points: &mut dyn Iterator<Item = Vec2>| points
+            .map(|p| (p, distance(p)))
+            .filter(|(_, d)| *d <= PICK_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(p, _)| p);
+        let edges = || self.edges().filter(|(v, _)| kept(*v)).map(|(_, e)| e);
+        let mut vertices = edges().map(|e| e.from).chain(self.new_polygon_part.iter().copied());
+        if let Some(p) = nearest(&mut vertices) { return (p, true) }
+        let mut on_edges = edges().map(|e| e.closest_point(world));
+        if let Some(p) = nearest(&mut on_edges) { return (p, true) }
+        if self.grid_snap {
+            let step = self.snap_step();
+            return (Vec2::new((world.x / step).round() * step, (world.y / step).round() * step), true)
+        }
+        (world, false)
+    }
+
+    /// adds a vertex to the ring being drawn
+    fn add_vertex(&mut self, point: Vec2) {
+        if self.state == UiState::InputDone { return }
+        if let Some(x) = self.new_polygon_part.last() {
+            if (self.camera.to_screen(*x) - self.camera.to_screen(point)).magnitude_squared() < 1.0 {
+                return; // avoid malformed edges
+            }
+        }
+        self.execute(Command::AddVertex(point))
+    }
+
+    /// clips again after an edit, once both polygons are in. stepping stays at the same step
+    fn update_result(&mut self) {
+        self.result_fill = None;
+        self.results.clear();
+        if self.state != UiState::InputDone {
+            self.result_polygon.clear();
+            self.steps = None;
+            return
+        }
+        let symbol = op_info(self.op).symbol;
+        if self.subject_polygons.len() != 1 || self.clipping_polygons.len() != 1 {
+            self.steps = None;
+        }
+        for (i, subject) in self.subject_polygons.iter().enumerate() {
+            for (j, clipping) in self.clipping_polygons.iter().enumerate() {
+                let (subject, clipping) = (subject.edges.clone(), clipping.edges.clone());
+                let result = if self.op != BooleanOp::Intersection {
+                    boolean_op(&subject, &clipping, self.op)
+                } else {
+                    let (result, trace) = clip_polygon_traced(&subject, &clipping);
+                    if let Some(steps) = &mut self.steps {
+                        *steps = StepView { shown: steps.shown.min(trace.len()), trace, subject, clipping };
+                    }
+                    result
+                };
+                if !result.is_empty() {
+                    self.results.push((format!("S{} {} C{}", i + 1, symbol, j + 1), result));
+                }
+            }
+        }
+        self.result_polygon = self.results.iter().flat_map(|(_, result)| result.iter().copied()).collect();
+    }
+
+    fn drawing(&self) -> Drawing {
+        Drawing {
+            state: self.state,
+            subject: self.subject_polygons.clone(),
+            clipping: self.clipping_polygons.clone(),
+            new_polygon_part: self.new_polygon_part.clone()
+        }
+    }
+
+    fn set_drawing(&mut self, drawing: Drawing) {
+        self.state = drawing.state;
+        self.subject_polygons = drawing.subject;
+        self.clipping_polygons = drawing.clipping;
+        self.new_polygon_part = drawing.new_polygon_part;
+    }
+
+    fn apply(&mut self, command: &Command) {
+        match command {
+            Command::AddVertex(point) => self.new_polygon_part.push(*point),
+            Command::CloseRing { role, after, .. } => {
+                *self.polygons_mut(*role) = after.clone();
+                self.new_polygon_part.clear();
+            }
+            Command::CancelRing(_) => self.new_polygon_part.clear(),
+            Command::MoveVertex { vertex, to, .. } => self.polygon_mut(*vertex).move_vertex(vertex.edge, *to),
+            Command::InsertVertex { vertex, at } => self.polygon_mut(*vertex).insert_vertex(vertex.edge - 1, *at),
+            Command::RemoveVertex { vertex, .. } => {
+                self.polygon_mut(*vertex).remove_vertex(vertex.edge);
+                if self.polygon(*vertex).is_empty() {
+                    self.polygons_mut(vertex.role).remove(vertex.polygon);
+                }
+            }
+            Command::SetState { to, .. } => self.state = *to,
+            Command::Replace { after, .. } => self.set_drawing(after.clone())
+        }
+        self.update_result();
+    }
+
+    fn revert(&mut self, command: &Command) {
+        match command {
+            Command::AddVertex(_) => { self.new_polygon_part.pop(); },
+            Command::CloseRing { role, points, before, .. } => {
+                *self.polygons_mut(*role) = before.clone();
+                self.new_polygon_part = points.clone();
+            }
+            Command::CancelRing(points) => self.new_polygon_part = points.clone(),
+            Command::MoveVertex { vertex, from, .. } => self.polygon_mut(*vertex).move_vertex(vertex.edge, *from),
+            Command::InsertVertex { vertex, .. } => self.polygon_mut(*vertex).remove_vertex(vertex.edge),
+            Command::RemoveVertex { vertex, before } => *self.polygons_mut(vertex.role) = before.clone(),
+            Command::SetState { from, .. } => self.state = *from,
+            Command::Replace { before, .. } => self.set_drawing(before.clone())
+        }
+        self.update_result();
+    }
+
+    fn execute(&mut self, command: Command) {
+        self.message = None;
+        self.let_go();
+        self.apply(&command);
+        self.record(command);
+    }
+
+    /// keeps a command that has already been applied
+    fn record(&mut self, command: Command) {
+        self.undo_stack.push(command);
+        self.redo_stack.clear();
+    }
+
+    /// forgets the press, drag, and grabbed and selected vertices, which may no longer be there
+    fn let_go(&mut self) {
+        self.selected = None;
+        self.pressed_at = None;
+        self.dragging = false;
+        self.grabbed = None;
+    }
+
+    fn undo(&mut self) {
+        let Some(command) = self.undo_stack.pop() else { return };
+        self.let_go();
+        self.revert(&command);
+        self.redo_stack.push(command);
+    }
+
+    fn redo(&mut self) {
+        let Some(command) = self.redo_stack.pop() else { return };
+        self.let_go();
+        self.apply(&command);
+        self.undo_stack.push(command);
+    }
+
+    fn replace(&mut self, drawing: Drawing) {
+        self.execute(Command::Replace { before: self.drawing(), after: drawing });
+    }
+
     fn cancel_current_polygon(&mut self) {
         if self.new_polygon_part.is_empty() {
-            self.state = UiState::WaitSubject;
-            self.subject_polygon.clear();
-            self.clipping_polygon.clear();
-            self.result_polygon.clear();
+            self.replace(Drawing { state: WaitSubject, subject: vec![], clipping: vec![], new_polygon_part: vec![] })
         } else {
-            self.new_polygon_part.clear();
+            self.execute(Command::CancelRing(self.new_polygon_part.clone()))
         }
     }
+
+    fn fit_to_content(&mut self) {
+        let part: Vec<Edge> = self.new_polygon_part.iter().map(|p| Edge { from: *p, to: *p }).collect();
+        let polygons: Vec<&[Edge]> = self.subject_polygons.iter().chain(self.clipping_polygons.iter())
+            .map(|polygon| &polygon.edges[..])
+            .chain([&self.result_polygon[..], &part])
+            .collect();
+        self.camera = Camera::fit(&polygons, self.size.x, self.size.y)
+    }
+
+    /// saves to the scene file, or to the first free `scene-N.json` in the working directory
+    fn save_scene(&mut self) {
+        let path = self.scene_path.clone().unwrap_or_else(|| {
+            (1..).map(|n| PathBuf::from(format!("scene-{}.json", n))).find(|p| !p.exists()).unwrap()
+        });
+        let scene = Scene {
+            subject: self.subject_polygons.clone(),
+            clipping: self.clipping_polygons.clone(),
+            result: self.result_polygon.clone()
+        };
+        self.message = Some(match std::fs::write(&path, to_scene_json(&scene)) {
+            Ok(()) => format!("Saved {}", path.display()),
+            Err(e) => format!("Could not save {}: {}", path.display(), e)
+        });
+        self.scene_path = Some(path);
+    }
+
+    /// replaces the polygons with a generated scene, a different one each time, and says its seed
+    fn random_scene(&mut self) {
+        let seed = self.random_seed;
+        self.random_seed += 1;
+        let mut rng = Rng::new(seed);
+        let holes = 1 + rng.below(3);
+        let with_holes = random_with_holes(&mut rng, 16, holes, Vec2::new(300.0, 300.0), 150.0, 250.0);
+        let points = random_points(&mut rng, 12, Vec2::new(600.0, 100.0), Vec2::new(900.0, 400.0));
+        let mut subject = vec![Rings::new(with_holes)];
+        subject.extend(random_space_partitioning(&mut rng, &points).map(Rings::new));
+        let star = random_star(&mut rng, 20, Vec2::new(450.0, 350.0), 100.0, 300.0);
+        let points = random_points(&mut rng, 10, Vec2::new(200.0, 700.0), Vec2::new(700.0, 900.0));
+        let mut clipping = vec![Rings::new(star)];
+        clipping.extend(random_two_opt(&mut rng, &points).map(Rings::new));
+        self.replace(Drawing { state: UiState::InputDone, subject, clipping, new_polygon_part: vec![] });
+        self.fit_to_content();
+        self.message = Some(format!("Random scene, seed {}", seed));
+    }
+
+    /// replaces the polygons with the scene's, clipping again if both polygons are there
+    fn load_scene(&mut self, path: &Path) {
+        let scene = std::fs::read_to_string(path).map_err(|e| e.to_string())
+            .and_then(|text| parse_scene(&text).map_err(|e| e.to_string()));
+        let scene = match scene {
+            Ok(scene) => scene,
+            Err(e) => {
+                self.message = Some(format!("Could not load {}: {}", path.display(), e));
+                return
+            }
+        };
+        let state = if scene.subject.is_empty() {
+            WaitSubject
+        } else if scene.clipping.is_empty() {
+            UiState::WaitClipping
+        } else {
+            UiState::InputDone
+        };
+        self.replace(Drawing { state, subject: scene.subject, clipping: scene.clipping, new_polygon_part: vec![] });
+        self.fit_to_content();
+        self.message = Some(format!("Loaded {}", path.display()));
+    }
 }
 
-pub fn run_loop() {
+/// opens the viewer, showing the scene file if there is one.
+/// a file that does not exist yet is where Ctrl+S saves
+pub fn run_loop(scene: Option<PathBuf>) {
     let win = Window::new_centered("Polygon Clipping", (WIDTH, HEIGHT)).expect("Window creation failed");
-    win.run_loop(UiLogic::default())
+    let mut logic = UiLogic::default();
+    if let Some(path) = scene {
+        if path.exists() {
+            logic.load_scene(&path);
+        }
+        logic.scene_path = Some(path);
+    }
+    win.run_loop(logic)
 }
\ No newline at end of file
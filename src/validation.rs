@@ -0,0 +1,117 @@
+use std::fmt::{Display, Formatter};
+
+use speedy2d::dimen::Vec2;
+
+use crate::edge::Edge;
+use crate::polygon::{is_point_in_polygon, is_polygon_clockwise, split_rings};
+use crate::vec::{cross_product, inner_product};
+
+/// why a polygon is not valid. rings are numbered in the order they are stored
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    TooFewVertices { ring: usize },
+    SelfIntersection { ring: usize },
+    RingsIntersect { ring: usize, other: usize },
+    HoleOutsideShell { ring: usize }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::TooFewVertices { ring } => write!(f, "ring {} has fewer than 3 vertices", ring),
+            ValidationError::SelfIntersection { ring } => write!(f, "ring {} intersects itself", ring),
+            ValidationError::RingsIntersect { ring, other } => write!(f, "rings {} and {} intersect", ring, other),
+            ValidationError::HoleOutsideShell { ring } => write!(f, "hole {} is not inside an outer ring", ring)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// true if the edges meet anywhere but at the vertex where one ends and the other starts
+pub fn edges_cross (e1: &Edge, e2: &Edge) -> bool {
+    if e1.get_vector().magnitude_squared() <= f32::EPSILON || e2.get_vector().magnitude_squared() <= f32::EPSILON {
+        return false
+    }
+    let Some(point) = e1.intersect_with(e2) else { return false };
+    let v1 = e1.get_vector();
+    let v2 = e2.get_vector();
+    let touches_at = |shared: Vec2| {
+        (point - shared).magnitude_squared() <= f32::EPSILON
+            // folding back onto the other edge is still a crossing
+            && (cross_product(&v1, &v2).abs() > f32::EPSILON || inner_product(&v1, &v2) > 0.0)
+    };
+    let consecutive = (e1.to - e2.from).magnitude_squared() <= f32::EPSILON && touches_at(e1.to);
+    let consecutive_rev = (e2.to - e1.from).magnitude_squared() <= f32::EPSILON && touches_at(e1.from);
+    !consecutive && !consecutive_rev
+}
+
+/// a ring is simple if no two edges cross, apart from consecutive edges sharing a vertex
+pub fn is_ring_simple (ring: &[Edge]) -> bool {
+    for (i, e1) in ring.iter().enumerate() {
+        for e2 in ring.iter().skip(i + 1) {
+            if edges_cross(e1, e2) {
+                return false
+            }
+        }
+    }
+    true
+}
+
+/// checks that every ring is simple, no two rings touch or cross,
+/// and every hole (clockwise ring) lies inside an outer ring
+pub fn validate_polygon (polygon: &[Edge]) -> Result<(), ValidationError> {
+    let rings = split_rings(polygon);
+    for (i, ring) in rings.iter().enumerate() {
+        if ring.len() < 3 {
+            return Err(ValidationError::TooFewVertices { ring: i })
+        }
+        if !is_ring_simple(ring) {
+            return Err(ValidationError::SelfIntersection { ring: i })
+        }
+    }
+    for (i, ring) in rings.iter().enumerate() {
+        for (j, other) in rings.iter().enumerate().skip(i + 1) {
+            if ring.iter().any(|e1| other.iter().any(|e2| e1.intersect_with(e2).is_some())) {
+                return Err(ValidationError::RingsIntersect { ring: i, other: j })
+            }
+        }
+    }
+    for (i, ring) in rings.iter().enumerate() {
+        if !is_polygon_clockwise(ring) { continue }
+        let inside_shell = rings.iter()
+            .filter(|other| !is_polygon_clockwise(other))
+            .any(|outer| is_point_in_polygon(ring[0].from, outer));
+        if !inside_shell {
+            return Err(ValidationError::HoleOutsideShell { ring: i })
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::ring;
+    use crate::validation::{is_ring_simple, validate_polygon, ValidationError};
+
+    #[test]
+    fn test_simple_rings() {
+        assert!(is_ring_simple(&ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)])), "fail on square");
+        assert!(is_ring_simple(&ring(&[(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (2.0, 0.0)])), "fail on collinear");
+        assert!(!is_ring_simple(&ring(&[(0.0, 0.0), (1.0, 1.0), (1.0, 0.0), (0.0, 1.0)])), "fail on bow tie");
+        assert!(!is_ring_simple(&ring(&[(0.0, 0.0), (0.0, 2.0), (0.0, 1.0), (1.0, 0.0)])), "fail on spike");
+    }
+
+    #[test]
+    fn test_validate() {
+        let outer = ring(&[(0.0, 0.0), (0.0, 4.0), (4.0, 4.0), (4.0, 0.0)]);
+        let hole = ring(&[(1.0, 1.0), (2.0, 1.0), (2.0, 2.0), (1.0, 2.0)]);
+        let far_hole = ring(&[(5.0, 5.0), (6.0, 5.0), (6.0, 6.0), (5.0, 6.0)]);
+        let crossing_hole = ring(&[(3.0, 3.0), (5.0, 3.0), (5.0, 5.0), (3.0, 5.0)]);
+        assert_eq!(validate_polygon(&[outer.clone(), hole.clone()].concat()), Ok(()));
+        assert_eq!(validate_polygon(&[outer.clone(), far_hole].concat()),
+                   Err(ValidationError::HoleOutsideShell { ring: 1 }));
+        assert_eq!(validate_polygon(&[outer, hole, crossing_hole].concat()),
+                   Err(ValidationError::RingsIntersect { ring: 0, other: 2 }));
+    }
+}